@@ -0,0 +1,62 @@
+%
+(Example job: face a 60 x 40 mm block of aluminium, then cut a pocket with)
+(rounded corners and a circular boss in it.)
+(Tool numbers refer to the tool catalogue, starting at 1.)
+G21 G90 G17
+
+(Facing, 6 mm single-flute cutter)
+T12 M6
+S18000 M3
+G0 Z5
+G0 X-5 Y2
+G1 Z-0.5 F300
+G1 X65 F1200
+G1 Y7
+G1 X-5
+G1 Y12
+G1 X65
+G1 Y17
+G1 X-5
+G1 Y22
+G1 X65
+G1 Y27
+G1 X-5
+G1 Y32
+G1 X65
+G1 Y37
+G1 X-5
+G0 Z5
+
+(Pocket, 4 mm two-flute cutter, 1 mm steps down)
+T18 M6
+S20000 M3
+G0 X15 Y12
+G1 Z-1.5 F200
+G1 X45 F900
+G3 X48 Y15 I0 J3
+G1 Y25
+G3 X45 Y28 I-3 J0
+G1 X15
+G3 X12 Y25 I0 J-3
+G1 Y15
+G3 X15 Y12 I3 J0
+G1 Z-2.5 F200
+G1 X45 F900
+G3 X48 Y15 R3
+G1 Y25
+G3 X45 Y28 R3
+G1 X15
+G3 X12 Y25 R3
+G1 Y15
+G3 X15 Y12 R3
+
+(Boss in the middle of the pocket, climb milling around it)
+G0 Z5
+G0 X22 Y20
+G1 Z-2.5 F200
+G2 X22 Y20 I8 J0 F900
+G0 Z5
+M5
+G0 X0 Y0
+M30
+%
@@ -0,0 +1,743 @@
+//! The analysis of a design, as values that can be compared
+//!
+//! `cnc` prints everything it computes as it goes. That's fine for reading
+//! through, but when iterating on a design, what matters is what changed. This
+//! goes through the same [`Design`], and collects inputs, outputs, and
+//! pass/fail checks, so two designs can be diffed.
+
+use std::fmt;
+
+use crate::{
+    budget::Budget,
+    config::MachineConfig,
+    design::Design,
+    machine::{
+        axes::Axis,
+        drives::{self, AxisDrive},
+        limits::AxisLimits,
+        resolution::{Resolution, RiskLevel},
+    },
+    toolpath::{
+        planner::{Planner, Profile},
+        Toolpath,
+    },
+    TangentialCuttingForce,
+};
+
+/// The result of analyzing a design
+pub struct Analysis {
+    pub inputs: Vec<(String, Value)>,
+    pub outputs: Vec<(String, Value)>,
+    pub checks: Vec<Check>,
+}
+
+impl Analysis {
+    /// Analyze a design, and how long it takes to run a program, if any
+    pub fn new(
+        config: &MachineConfig,
+        design: &Design,
+        toolpath: Option<&Toolpath>,
+    ) -> Self {
+        let mut analysis = Self {
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            checks: Vec::new(),
+        };
+        analysis.add_inputs(config);
+
+        let tools = &design.tools;
+        let rail = &design.rail;
+        let constraints = &design.constraints;
+        let cutting_force = design.cutting_force();
+        let acceleration = drives::design_acceleration();
+
+        let overloading = tools
+            .iter()
+            .filter(|tool| design.overloads_spindle(tool))
+            .count();
+        analysis.check(
+            "spindle torque",
+            overloading == 0,
+            format!(
+                "{overloading} tools need more torque than the spindle has"
+            ),
+        );
+
+        let tool = &design.worst_case_tool;
+        analysis.text("worst-case tool", tool.to_string());
+        analysis.number(
+            "worst-case cutting force",
+            cutting_force.value_n(),
+            "N",
+        );
+        analysis.text(
+            "worst-case cutting force limited by",
+            match design.worst_case_force {
+                TangentialCuttingForce::PerToolRequirements(_) => "tool",
+                TangentialCuttingForce::PerMaxSpindleTorque(_) => "spindle",
+            }
+            .to_string(),
+        );
+
+        for (axis, min_travel) in [Axis::X, Axis::Y, Axis::Z]
+            .into_iter()
+            .zip(constraints.min_travel)
+        {
+            let travel = design.layout.envelope(axis).travel();
+            analysis.number(
+                format!("{axis}-axis travel"),
+                travel.value_mm(),
+                "mm",
+            );
+            analysis.check(
+                format!("{axis}-axis travel"),
+                travel >= min_travel,
+                format!("at least {} mm", min_travel.value_mm()),
+            );
+        }
+
+        let stack_up = design.stack_up();
+        analysis.number("spindle nose min", stack_up.nose_min.value_mm(), "mm");
+        analysis.number("spindle nose max", stack_up.nose_max.value_mm(), "mm");
+        let not_reaching = tools
+            .iter()
+            .filter(|tool| !stack_up.reaches_table(tool))
+            .count();
+        analysis.check(
+            "tools reach the table",
+            not_reaching == 0,
+            format!("{not_reaching} tools can't reach the table"),
+        );
+
+        for axis in &design.kinematics.axes {
+            let name = axis.axis;
+            let load = rail.carriage_load(
+                cutting_force,
+                axis.lever_arm,
+                axis.carriage_spacing,
+            );
+            let safety_factor = rail.static_safety_factor(load);
+
+            analysis.number(
+                format!("{name}-axis moving mass"),
+                axis.moving_mass.value_kg(),
+                "kg",
+            );
+            analysis.number(
+                format!("{name}-axis rail moment"),
+                axis.rail_moment(cutting_force, acceleration).value_nm(),
+                "Nm",
+            );
+            analysis.number(
+                format!("{name}-axis carriage load"),
+                load.value_n(),
+                "N",
+            );
+            analysis.number(
+                format!("{name}-axis rail safety factor"),
+                safety_factor,
+                "",
+            );
+            analysis.check(
+                format!("{name}-axis rail safety factor"),
+                safety_factor >= constraints.rail_safety_factor,
+                format!("at least {}", constraints.rail_safety_factor),
+            );
+        }
+
+        for drive in &design.drives {
+            analysis.add_drive(design, drive);
+        }
+
+        let electronics = &design.electronics;
+        let required_power = electronics.required_power(&design.drives);
+        analysis.number("required power", required_power.value_w(), "W");
+        analysis.check(
+            "power supply",
+            required_power.value_w()
+                <= electronics.power_supply.power.value_w(),
+            format!("{} available", electronics.power_supply.power),
+        );
+        let problems = electronics.check(&design.drives);
+        analysis.check(
+            "electronics",
+            problems.is_empty(),
+            problems
+                .iter()
+                .map(|problem| problem.to_string())
+                .collect::<Vec<_>>()
+                .join("; "),
+        );
+
+        for direction in [Axis::X, Axis::Y] {
+            let stiffness =
+                design.compliance(direction, tool).total_stiffness();
+            analysis.number(
+                format!("tool-tip stiffness ({direction})"),
+                stiffness.value_n_per_um(),
+                "N/µm",
+            );
+            analysis.number(
+                format!("tool-tip deflection ({direction})"),
+                stiffness.to_deflection(cutting_force).value_mm() * 1000.,
+                "µm",
+            );
+        }
+
+        let (_, lobes) = design.chatter(tool);
+        analysis.number(
+            "stable depth of worst-case tool",
+            lobes.critical_depth.value_mm(),
+            "mm",
+        );
+
+        let budget = Budget::new(&design.bom(), design.budget);
+        analysis.number("cost", budget.total(), "€");
+        analysis.check(
+            "budget",
+            budget.headroom() >= 0.,
+            format!("at most {:.2} €", budget.limit),
+        );
+
+        if let Some(toolpath) = toolpath {
+            let plan = Planner::new(&design.limits(), Profile::Trapezoidal)
+                .plan(toolpath);
+            analysis.number("cycle time", plan.cycle_time().as_secs_f64(), "s");
+        }
+
+        analysis
+    }
+
+    fn add_inputs(&mut self, config: &MachineConfig) {
+        let mut input = |name: &str, value: Value| {
+            self.inputs.push((name.to_string(), value));
+        };
+        let mm = |value: f64| Value::Number(value, "mm");
+
+        input(
+            "configuration",
+            Value::Text(config.configuration.to_string()),
+        );
+        for (axis, value) in ["x", "y", "z"].iter().zip(config.outer_dimensions)
+        {
+            input(&format!("outer dimension ({axis})"), mm(value));
+        }
+        input("plate thickness", mm(config.plate_thickness));
+        for (axis, value) in ["x", "y"].iter().zip(config.table) {
+            input(&format!("table ({axis})"), mm(value));
+        }
+        input("frame", Value::Text(config.frame.clone()));
+        input("rail", Value::Text(config.rail.clone()));
+        input(
+            "spindle power",
+            Value::Number(config.spindle.power_kw, "kW"),
+        );
+        input(
+            "spindle min speed",
+            Value::Number(config.spindle.min_rpm, "rpm"),
+        );
+        input(
+            "spindle max speed",
+            Value::Number(config.spindle.max_rpm, "rpm"),
+        );
+        for (axis, drive) in config.drives.iter() {
+            input(
+                &format!("{axis}-axis driver"),
+                Value::Text(drive.driver.clone()),
+            );
+            input(
+                &format!("{axis}-axis microsteps"),
+                Value::Number(f64::from(drive.microsteps), ""),
+            );
+            input(
+                &format!("{axis}-axis motor"),
+                Value::Text(drive.motor.clone()),
+            );
+            input(
+                &format!("{axis}-axis screw"),
+                Value::Text(drive.screw.clone()),
+            );
+            input(&format!("{axis}-axis screw length"), mm(drive.screw_length));
+        }
+        input(
+            "power supply",
+            Value::Text(config.electronics.power_supply.clone()),
+        );
+        input(
+            "controller power",
+            Value::Number(config.electronics.controller_power_w, "W"),
+        );
+        input(
+            "tools",
+            Value::Text(match &config.tools {
+                Some(path) => path.display().to_string(),
+                None => "built-in".to_string(),
+            }),
+        );
+        input(
+            "number of tools",
+            Value::Number(config.tools().len() as f64, ""),
+        );
+        input("budget", Value::Number(config.budget, "€"));
+        input(
+            "motor torque safety factor",
+            Value::Number(config.safety_factors.motor_torque, ""),
+        );
+        input(
+            "rail static safety factor",
+            Value::Number(config.safety_factors.rail_static, ""),
+        );
+        input(
+            "screw life",
+            Value::Number(config.safety_factors.screw_life_km, "km"),
+        );
+    }
+
+    fn add_drive(&mut self, design: &Design, drive: &AxisDrive) {
+        let cutting_force = design.cutting_force();
+        let supply_voltage = design.supply_voltage();
+        let constraints = &design.constraints;
+        let axis = drive.axis;
+        let acceleration = drives::design_acceleration();
+
+        let axial_force = drive.axial_force(cutting_force, acceleration);
+        self.number(
+            format!("{axis}-axis axial force"),
+            axial_force.value_n(),
+            "N",
+        );
+        self.number(
+            format!("{axis}-axis required torque"),
+            drive
+                .required_torque(cutting_force, acceleration)
+                .value_nm(),
+            "Nm",
+        );
+
+        let screw_life = drive.screw.rated_life(axial_force);
+        self.number(
+            format!("{axis}-axis screw life"),
+            screw_life.value_m() / 1000.,
+            "km",
+        );
+        self.check(
+            format!("{axis}-axis screw life"),
+            screw_life >= constraints.screw_life,
+            format!("at least {} km", constraints.screw_life.value_m() / 1000.),
+        );
+
+        let margin = drive.torque_margin(
+            constraints.min_feed,
+            cutting_force,
+            acceleration,
+            supply_voltage,
+        );
+        self.number(
+            format!("{axis}-axis torque margin at {}", constraints.min_feed),
+            margin,
+            "",
+        );
+        self.check(
+            format!("{axis}-axis torque margin"),
+            margin >= drive.safety_factor,
+            format!(
+                "at least {} at {}",
+                drive.safety_factor, constraints.min_feed
+            ),
+        );
+
+        let inertia_ratio = drive.inertia_ratio();
+        self.number(format!("{axis}-axis inertia ratio"), inertia_ratio, "");
+        self.check(
+            format!("{axis}-axis inertia ratio"),
+            inertia_ratio <= AxisDrive::MAX_INERTIA_RATIO,
+            format!("at most {}", AxisDrive::MAX_INERTIA_RATIO),
+        );
+
+        let limits = AxisLimits::new(drive, cutting_force, supply_voltage);
+        self.number(
+            format!("{axis}-axis max feed"),
+            limits.max_feed_velocity.speed.value_mm_per_min(),
+            "mm/min",
+        );
+        self.number(
+            format!("{axis}-axis max rapid"),
+            limits.max_rapid_velocity.speed.value_mm_per_min(),
+            "mm/min",
+        );
+        self.number(
+            format!("{axis}-axis max acceleration"),
+            limits.max_acceleration.value_m_per_s2(),
+            "m/s²",
+        );
+
+        // Resolutions are a few µm, which would vanish in mm.
+        let resolution = Resolution::new(drive);
+        self.number(
+            format!("{axis}-axis microstep resolution"),
+            resolution.microstep.value_mm() * 1000.,
+            "µm",
+        );
+        self.number(
+            format!("{axis}-axis effective resolution"),
+            resolution.effective.value_mm() * 1000.,
+            "µm",
+        );
+
+        let at_risk = design
+            .stall_risks(drive)
+            .iter()
+            .filter(|risk| risk.level() != RiskLevel::Safe)
+            .count();
+        self.check(
+            format!("{axis}-axis stall risk"),
+            at_risk == 0,
+            format!("{at_risk} tools with too little margin"),
+        );
+    }
+
+    fn number(
+        &mut self,
+        name: impl Into<String>,
+        value: f64,
+        unit: &'static str,
+    ) {
+        self.outputs.push((name.into(), Value::Number(value, unit)));
+    }
+
+    fn text(&mut self, name: impl Into<String>, value: String) {
+        self.outputs.push((name.into(), Value::Text(value)));
+    }
+
+    fn check(&mut self, name: impl Into<String>, passed: bool, detail: String) {
+        self.checks.push(Check {
+            name: name.into(),
+            passed,
+            detail,
+        });
+    }
+}
+
+/// An input or output of an [`Analysis`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A number, and its unit
+    Number(f64, &'static str),
+
+    Text(String),
+}
+
+impl Value {
+    /// Compare, ignoring differences below the printed precision
+    ///
+    /// Numbers range from µm to thousands of mm/min, so that's relative.
+    fn approx_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Number(a, _), Value::Number(b, _)) => {
+                (a - b).abs() <= 1e-4 * a.abs().max(b.abs())
+            }
+            _ => self == other,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(value, unit) => {
+                // 4 significant digits, but never less than the whole number,
+                // and none for counts.
+                let decimals = if value.fract() == 0. {
+                    0
+                } else {
+                    (3 - value.abs().log10().floor() as i32).clamp(0, 6)
+                        as usize
+                };
+                write!(f, "{value:.decimals$}")?;
+                if !unit.is_empty() {
+                    write!(f, " {unit}")?;
+                }
+                Ok(())
+            }
+            Value::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+/// A requirement that a design passes or fails
+pub struct Check {
+    pub name: String,
+    pub passed: bool,
+
+    /// What the requirement is, or why it failed
+    pub detail: String,
+}
+
+/// The differences between two analyses
+pub struct Diff {
+    pub inputs: Vec<Change>,
+    pub outputs: Vec<Change>,
+
+    /// The checks, as they turned out for both designs
+    ///
+    /// A check that only one of the designs has is `None` for the other.
+    pub checks: Vec<(Option<Check>, Option<Check>)>,
+}
+
+impl Diff {
+    pub fn new(before: Analysis, after: Analysis) -> Self {
+        Self {
+            inputs: Change::between(before.inputs, after.inputs),
+            outputs: Change::between(before.outputs, after.outputs),
+            checks: pair(before.checks, after.checks, |check| &check.name),
+        }
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (title, changes) in
+            [("Inputs", &self.inputs), ("Outputs", &self.outputs)]
+        {
+            writeln!(f, "{title} ({} changed):", changes.len())?;
+            for change in changes {
+                writeln!(f, "  {change}")?;
+            }
+        }
+
+        let flipped = self
+            .checks
+            .iter()
+            .filter(|checks| match checks {
+                (Some(before), Some(after)) => before.passed != after.passed,
+                _ => false,
+            })
+            .count();
+        writeln!(f, "Checks ({flipped} flipped):")?;
+        for checks in &self.checks {
+            let (status, check) = match checks {
+                (Some(before), Some(after)) => {
+                    match (before.passed, after.passed) {
+                        (true, true) => continue,
+                        (false, true) => ("FIXED", before),
+                        (true, false) => ("BROKEN", after),
+                        (false, false) => ("still failing", after),
+                    }
+                }
+                (None, Some(after)) if after.passed => {
+                    ("added, passing", after)
+                }
+                (None, Some(after)) => ("added, FAILING", after),
+                (Some(before), None) => ("removed", before),
+                (None, None) => continue,
+            };
+            writeln!(f, "  {status}: {} ({})", check.name, check.detail)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A value that differs between two analyses
+///
+/// A value that only one of the analyses has is `None` for the other.
+pub struct Change {
+    pub name: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl Change {
+    fn between(
+        before: Vec<(String, Value)>,
+        after: Vec<(String, Value)>,
+    ) -> Vec<Self> {
+        pair(before, after, |(name, _)| name)
+            .into_iter()
+            .filter(|values| match values {
+                (Some((_, a)), Some((_, b))) => !a.approx_eq(b),
+                _ => true,
+            })
+            .map(|(before, after)| {
+                let (name, _) = before.as_ref().or(after.as_ref()).unwrap();
+                Change {
+                    name: name.clone(),
+                    before: before.map(|(_, value)| value),
+                    after: after.map(|(_, value)| value),
+                }
+            })
+            .collect()
+    }
+
+    /// The relative change, for numbers
+    pub fn relative(&self) -> Option<f64> {
+        match (&self.before, &self.after) {
+            (Some(Value::Number(before, _)), Some(Value::Number(after, _)))
+                if *before != 0. =>
+            {
+                Some((after - before) / before.abs())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.name)?;
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => write!(f, "{before} -> {after}")?,
+            (None, Some(after)) => write!(f, "added, {after}")?,
+            (Some(before), None) => write!(f, "removed, was {before}")?,
+            (None, None) => {}
+        }
+        if let Some(relative) = self.relative() {
+            write!(f, " ({:+.1}%)", relative * 100.)?;
+        }
+        Ok(())
+    }
+}
+
+/// Pair up the entries of two analyses by name
+///
+/// Keeps the order of `before`, with entries only in `after` at the end.
+fn pair<T>(
+    before: Vec<T>,
+    after: Vec<T>,
+    name: impl Fn(&T) -> &String,
+) -> Vec<(Option<T>, Option<T>)> {
+    let mut after = after.into_iter().map(Some).collect::<Vec<_>>();
+
+    let mut pairs = before
+        .into_iter()
+        .map(|entry| {
+            let other = after
+                .iter_mut()
+                .find(|other| {
+                    other
+                        .as_ref()
+                        .is_some_and(|other| name(other) == name(&entry))
+                })
+                .and_then(Option::take);
+            (Some(entry), other)
+        })
+        .collect::<Vec<_>>();
+    pairs.extend(after.into_iter().flatten().map(|entry| (None, Some(entry))));
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::MachineConfig, design::Design};
+
+    use super::{Analysis, Check, Diff, Value};
+
+    #[test]
+    fn entries_are_matched_by_name() {
+        let before = analysis(
+            &[("a", 1.), ("b", 2.), ("c", 3.)],
+            &[("a", true), ("b", true)],
+        );
+        let after = analysis(
+            &[("c", 3.), ("a", 1.5), ("d", 4.)],
+            &[("b", false), ("e", true)],
+        );
+
+        let diff = Diff::new(before, after);
+        let changes = diff
+            .outputs
+            .iter()
+            .map(|change| {
+                (
+                    change.name.as_str(),
+                    change.before.clone(),
+                    change.after.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                ("a", Some(number(1.)), Some(number(1.5))),
+                ("b", Some(number(2.)), None),
+                ("d", None, Some(number(4.))),
+            ],
+        );
+
+        let checks = diff
+            .checks
+            .iter()
+            .map(|(before, after)| {
+                let passed = |check: &Option<Check>| {
+                    check.as_ref().map(|check| check.passed)
+                };
+                (passed(before), passed(after))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            checks,
+            [
+                (Some(true), None),
+                (Some(true), Some(false)),
+                (None, Some(true))
+            ],
+        );
+    }
+
+    #[test]
+    fn small_values_are_compared_relative_to_their_size() {
+        assert!(!number(2.5).approx_eq(&number(2.501)));
+        assert!(number(5000.).approx_eq(&number(5000.1)));
+
+        assert_eq!(number(1.25e-3).to_string(), "0.001250 mm");
+        assert_eq!(number(5625.).to_string(), "5625 mm");
+        assert_eq!(number(0.).to_string(), "0 mm");
+        assert_eq!(number(32.).to_string(), "32 mm");
+    }
+
+    #[test]
+    fn microsteps_show_up_in_the_diff() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../machines");
+        let config =
+            MachineConfig::load(format!("{path}/fixed-gantry.toml")).unwrap();
+        let mut other =
+            MachineConfig::load(format!("{path}/fixed-gantry.toml")).unwrap();
+        other.drives.x.microsteps *= 2;
+
+        let diff = Diff::new(
+            Analysis::new(&config, &Design::new(&config).unwrap(), None),
+            Analysis::new(&other, &Design::new(&other).unwrap(), None),
+        );
+        let names = diff
+            .inputs
+            .iter()
+            .chain(&diff.outputs)
+            .map(|change| change.name.as_str())
+            .collect::<Vec<_>>();
+        assert!(names.contains(&"x-axis microsteps"));
+        assert!(names.contains(&"x-axis microstep resolution"));
+    }
+
+    fn analysis(outputs: &[(&str, f64)], checks: &[(&str, bool)]) -> Analysis {
+        Analysis {
+            inputs: Vec::new(),
+            outputs: outputs
+                .iter()
+                .map(|&(name, value)| (name.to_string(), number(value)))
+                .collect(),
+            checks: checks
+                .iter()
+                .map(|&(name, passed)| Check {
+                    name: name.to_string(),
+                    passed,
+                    detail: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn number(value: f64) -> Value {
+        Value::Number(value, "mm")
+    }
+}
@@ -0,0 +1,313 @@
+//! Bill of materials
+//!
+//! The README lists what each axis needs, but that list doesn't know which
+//! parts the calculations actually ended up with. This derives the parts list
+//! from the same drives, rails, spindle, and power supply that the model uses.
+
+use std::{fmt, fmt::Write};
+
+use crate::{
+    cost::Price,
+    machine::{
+        axes::Axis,
+        drives::AxisDrive,
+        electronics::Electronics,
+        layout::Layout,
+        spindle::{Spindle, SpindleGeometry},
+    },
+};
+
+/// A bill of materials
+pub struct Bom {
+    pub items: Vec<LineItem>,
+}
+
+impl Bom {
+    pub fn new(
+        layout: &Layout,
+        drives: &[AxisDrive],
+        spindle: &Spindle,
+        spindle_geometry: &SpindleGeometry,
+        electronics: &Electronics,
+    ) -> Self {
+        let mut bom = Self { items: Vec::new() };
+
+        for drive in drives {
+            let axis = Some(drive.axis);
+
+            // Each axis runs on 2 rails with 2 carriages each.
+            let rail = &layout.rail;
+            let rail_length = layout.rail_length(drive.axis);
+            bom.add(
+                Category::Motion,
+                format!("{} rail", rail.name),
+                format!("{} mm", rail_length.value_mm()),
+                rail.price_per_meter.scale(rail_length.value_m()),
+                2,
+                axis,
+            );
+            bom.add(
+                Category::Motion,
+                format!("{} carriage", rail.name),
+                format!("{} N/µm", rail.carriage_stiffness.value_n_per_um()),
+                rail.carriage_price,
+                4,
+                axis,
+            );
+
+            let screw = &drive.screw;
+            let [fixed, supported] = screw.end_supports;
+            let [nut_price, housing_price] = screw.nut_prices;
+            let [fixed_price, supported_price] = screw.end_support_prices;
+            bom.add(
+                Category::Motion,
+                format!("{} ball screw", screw.name),
+                format!(
+                    "{} mm, {} mm lead",
+                    drive.screw_length.value_mm(),
+                    screw.lead.value_mm(),
+                ),
+                screw.price_per_meter.scale(drive.screw_length.value_m()),
+                1,
+                axis,
+            );
+            for (name, spec, price) in [
+                ("flanged nut", String::new(), nut_price),
+                ("nut housing", String::new(), housing_price),
+                ("fixed bearing", fixed.to_string(), fixed_price),
+                ("supported bearing", supported.to_string(), supported_price),
+            ] {
+                bom.add(
+                    Category::Motion,
+                    format!("{} {name}", screw.name),
+                    spec,
+                    price,
+                    1,
+                    axis,
+                );
+            }
+
+            let motor = &drive.motor;
+            bom.add(
+                Category::Drive,
+                format!("{} stepper motor", motor.name),
+                format!("{}, {}", motor.frame, motor.holding_torque),
+                motor.price,
+                1,
+                axis,
+            );
+            bom.add(
+                Category::Drive,
+                format!("{} stepper driver", drive.driver.name),
+                format!("{} microsteps", drive.microsteps),
+                drive.driver.price,
+                1,
+                axis,
+            );
+        }
+
+        let spindle_diameter = spindle_geometry
+            .segments
+            .iter()
+            .map(|segment| segment.diameter.to_length().value_mm())
+            .fold(0., f64::max);
+        let [spindle_price, vfd_price, clamp_price] = Spindle::prices();
+        bom.add(
+            Category::Spindle,
+            format!("{} air-cooled spindle", spindle.power()),
+            format!(
+                "{spindle_diameter} mm, {} to {}, {}",
+                spindle.min_speed, spindle.max_speed, spindle_geometry.mass,
+            ),
+            spindle_price,
+            1,
+            None,
+        );
+        bom.add(
+            Category::Spindle,
+            format!("{} VFD", spindle.power()),
+            String::new(),
+            vfd_price,
+            1,
+            None,
+        );
+        bom.add(
+            Category::Spindle,
+            "Spindle clamp".to_string(),
+            format!(
+                "{spindle_diameter} mm bore, {} mm wide",
+                layout.spindle_clamp_width.value_mm(),
+            ),
+            clamp_price,
+            1,
+            None,
+        );
+
+        let power_supply = &electronics.power_supply;
+        bom.add(
+            Category::Electronics,
+            power_supply.name.to_string(),
+            format!("{}, {}", power_supply.voltage, power_supply.power),
+            power_supply.price,
+            1,
+            None,
+        );
+
+        // Items were added axis by axis. Let's keep similar ones together.
+        bom.items
+            .sort_by(|a, b| (a.category, &a.name).cmp(&(b.category, &b.name)));
+
+        bom
+    }
+
+    /// Add an item, or add to the quantity of an identical one
+    fn add(
+        &mut self,
+        category: Category,
+        name: String,
+        spec: String,
+        unit_price: Price,
+        quantity: u32,
+        axis: Option<Axis>,
+    ) {
+        let existing = self.items.iter_mut().find(|item| {
+            item.category == category && item.name == name && item.spec == spec
+        });
+
+        let item = match existing {
+            Some(item) => {
+                item.quantity += quantity;
+                item
+            }
+            None => {
+                self.items.push(LineItem {
+                    category,
+                    name,
+                    spec,
+                    unit_price,
+                    quantity,
+                    axes: Vec::new(),
+                });
+                self.items.last_mut().unwrap()
+            }
+        };
+
+        item.axes.extend(axis);
+    }
+
+    /// The number of parts of the given name, across all line items
+    pub fn quantity_of(&self, name: &str) -> u32 {
+        self.items
+            .iter()
+            .filter(|item| item.name.ends_with(name))
+            .map(|item| item.quantity)
+            .sum()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        writeln!(
+            csv,
+            "Category,Part,Specification,Quantity,Axes,Vendor,Unit Price,\
+            Currency"
+        )
+        .unwrap();
+        for item in &self.items {
+            let fields = [
+                item.category.to_string(),
+                item.name.clone(),
+                item.spec.clone(),
+                item.quantity.to_string(),
+                item.axes_list(),
+                item.unit_price.vendor.to_string(),
+                format!("{:.2}", item.unit_price.value),
+                item.unit_price.currency.to_string(),
+            ];
+            let fields = fields
+                .iter()
+                .map(|field| {
+                    if field.contains([',', '"']) {
+                        format!("\"{}\"", field.replace('"', "\"\""))
+                    } else {
+                        field.clone()
+                    }
+                })
+                .collect::<Vec<_>>();
+            writeln!(csv, "{}", fields.join(",")).unwrap();
+        }
+        csv
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        writeln!(markdown, "# Bill of Materials").unwrap();
+        writeln!(markdown).unwrap();
+        writeln!(markdown, "Generated by the model in `model/`. Don't edit!")
+            .unwrap();
+        writeln!(markdown).unwrap();
+        writeln!(
+            markdown,
+            "| Category | Part | Specification | Qty | Axes | Vendor | Unit \
+            Price |"
+        )
+        .unwrap();
+        writeln!(markdown, "| --- | --- | --- | ---: | --- | --- | ---: |")
+            .unwrap();
+        for item in &self.items {
+            writeln!(
+                markdown,
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                item.category,
+                item.name,
+                item.spec,
+                item.quantity,
+                item.axes_list(),
+                item.unit_price.vendor,
+                item.unit_price,
+            )
+            .unwrap();
+        }
+        markdown
+    }
+}
+
+/// A line of a [`Bom`]
+pub struct LineItem {
+    pub category: Category,
+    pub name: String,
+    pub spec: String,
+    pub unit_price: Price,
+    pub quantity: u32,
+
+    /// The axes the item is used in, if it belongs to any
+    pub axes: Vec<Axis>,
+}
+
+impl LineItem {
+    fn axes_list(&self) -> String {
+        self.axes
+            .iter()
+            .map(|axis| axis.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Category {
+    Motion,
+    Drive,
+    Spindle,
+    Electronics,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Category::Motion => write!(f, "Motion"),
+            Category::Drive => write!(f, "Drive"),
+            Category::Spindle => write!(f, "Spindle"),
+            Category::Electronics => write!(f, "Electronics"),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Tracking the cost of the bill of materials against the budget
+
+use crate::{
+    bom::{Bom, Category},
+    cost::Vendor,
+};
+
+/// The cost of a [`Bom`], compared to a budget
+pub struct Budget {
+    /// The budget, in €
+    pub limit: f64,
+
+    /// The orders that the parts are bought with, one per vendor
+    pub orders: Vec<Order>,
+
+    /// The cost of each category, in €
+    ///
+    /// Shipping and import charges are split between the parts of an order,
+    /// according to their value.
+    pub categories: Vec<(Category, f64)>,
+}
+
+impl Budget {
+    pub fn new(bom: &Bom, limit: f64) -> Self {
+        let mut orders: Vec<Order> = Vec::new();
+        for item in &bom.items {
+            let vendor = item.unit_price.vendor;
+            let value = item.unit_price.value_eur() * f64::from(item.quantity);
+
+            match orders.iter_mut().find(|order| order.vendor.name == vendor) {
+                Some(order) => order.parts += value,
+                None => orders.push(Order {
+                    vendor: Vendor::by_name(vendor),
+                    parts: value,
+                    shipping: 0.,
+                    import_charges: 0.,
+                }),
+            }
+        }
+        for order in &mut orders {
+            order.shipping = order.vendor.shipping;
+            order.import_charges = order
+                .vendor
+                .source
+                .import_charges(order.parts + order.shipping);
+        }
+
+        let mut categories: Vec<(Category, f64)> = Vec::new();
+        for item in &bom.items {
+            let order = orders
+                .iter()
+                .find(|order| order.vendor.name == item.unit_price.vendor)
+                .unwrap();
+            let cost = item.unit_price.value_eur()
+                * f64::from(item.quantity)
+                * order.total()
+                / order.parts;
+
+            match categories
+                .iter_mut()
+                .find(|(category, _)| *category == item.category)
+            {
+                Some((_, total)) => *total += cost,
+                None => categories.push((item.category, cost)),
+            }
+        }
+        categories.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        Self {
+            limit,
+            orders,
+            categories,
+        }
+    }
+
+    /// The total cost, including shipping and import charges, in €
+    pub fn total(&self) -> f64 {
+        self.orders.iter().map(|order| order.total()).sum()
+    }
+
+    /// What's left of the budget, in €
+    ///
+    /// Negative, if the budget is exceeded.
+    pub fn headroom(&self) -> f64 {
+        self.limit - self.total()
+    }
+}
+
+/// An order from a single vendor
+pub struct Order {
+    pub vendor: Vendor,
+
+    /// The value of the parts, in €
+    pub parts: f64,
+
+    /// The shipping cost, in €
+    pub shipping: f64,
+
+    /// Import VAT and customs duties, in €
+    pub import_charges: f64,
+}
+
+impl Order {
+    /// The total cost of the order, in €
+    pub fn total(&self) -> f64 {
+        self.parts + self.shipping + self.import_charges
+    }
+}
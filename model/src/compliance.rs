@@ -0,0 +1,218 @@
+//! Compliance budget from tool tip to table
+//!
+//! The deflection between tool and workpiece is the sum of the deflections of
+//! everything in between. Each of those elements acts like a spring, and all
+//! of them are in series, so their compliances (the inverse of stiffness) add
+//! up. That makes it easy to see which element is the weakest link.
+
+use std::fmt;
+
+use crate::{
+    frame::FixedGantryFrame,
+    machine::{
+        axes::{self, Axis},
+        drives::AxisDrive,
+        rails::Rail,
+    },
+    physics::Stiffness,
+    tools::Tool,
+};
+
+/// The chain of elements between tool tip and table
+pub struct ComplianceChain {
+    /// The direction of the force that the chain is loaded with
+    pub direction: Axis,
+    pub elements: Vec<Element>,
+}
+
+impl ComplianceChain {
+    /// Set up the chain for a force in the given direction
+    pub fn new(
+        direction: Axis,
+        tool: &Tool,
+        rail: &Rail,
+        frame: &FixedGantryFrame,
+        drives: &[AxisDrive],
+    ) -> Self {
+        let drive = |axis| drives.iter().find(|d| d.axis == axis).unwrap();
+
+        let mut chain = Self {
+            direction,
+            elements: Vec::new(),
+        };
+
+        chain.add("tool", Source::Tool, tool.stiffness());
+
+        // I couldn't find any data on these, so they're educated guesses. If
+        // any of them turn out to dominate, they need a closer look.
+        chain.add(
+            "collet",
+            Source::Estimate,
+            Stiffness::from_value_n_per_um(40.),
+        );
+        chain.add(
+            "spindle bearings",
+            Source::Estimate,
+            Stiffness::from_value_n_per_um(40.),
+        );
+        chain.add(
+            "spindle clamp",
+            Source::Estimate,
+            Stiffness::from_value_n_per_um(60.),
+        );
+
+        // The z-axis rails run vertically, so forces in x and y both tilt the
+        // carriages along the rails.
+        chain.add(
+            "z carriages",
+            Source::Rails,
+            rail.axis_stiffness(
+                axes::z::carriage_to_tool_tip(),
+                axes::z::carriage_spacing(),
+            ),
+        );
+
+        // A force along an axis is taken up by its ball screw, a force across
+        // it by its rails.
+        match direction {
+            Axis::X => chain.add_screw(drive(Axis::X)),
+            _ => chain.add(
+                "x carriages",
+                Source::Rails,
+                rail.axis_stiffness(frame.tool_offset, axes::x::rail_spacing()),
+            ),
+        }
+
+        let frame_compliance = frame.compliance(direction);
+        chain.add(
+            "gantry",
+            Source::Frame,
+            Stiffness::from_compliance_m_per_n(frame_compliance.gantry),
+        );
+        chain.add(
+            "columns",
+            Source::Frame,
+            Stiffness::from_compliance_m_per_n(frame_compliance.columns),
+        );
+        chain.add(
+            "frame joints",
+            Source::Frame,
+            Stiffness::from_compliance_m_per_n(frame_compliance.joints),
+        );
+
+        chain.add(
+            "base",
+            Source::Estimate,
+            Stiffness::from_value_n_per_um(100.),
+        );
+
+        match direction {
+            Axis::Y => chain.add_screw(drive(Axis::Y)),
+            _ => chain.add(
+                "y carriages",
+                Source::Rails,
+                rail.axis_stiffness(
+                    axes::y::max_distance_to_force(rail),
+                    axes::y::rail_spacing(),
+                ),
+            ),
+        }
+
+        chain.add(
+            "table",
+            Source::Estimate,
+            Stiffness::from_value_n_per_um(200.),
+        );
+
+        chain
+    }
+
+    fn add(
+        &mut self,
+        name: &'static str,
+        source: Source,
+        stiffness: Stiffness,
+    ) {
+        self.elements.push(Element {
+            name,
+            source,
+            stiffness,
+        });
+    }
+
+    fn add_screw(&mut self, drive: &AxisDrive) {
+        // Worst case is the nut at the far end from the fixed bearing.
+        let name = match drive.axis {
+            Axis::X => "x-axis ball screw",
+            Axis::Y => "y-axis ball screw",
+            Axis::Z => "z-axis ball screw",
+        };
+        self.add(
+            name,
+            Source::BallScrew,
+            drive.screw.axial_stiffness(drive.screw_length),
+        );
+        self.add(
+            "ball nut and bearings",
+            Source::Estimate,
+            Stiffness::from_value_n_per_um(150.),
+        );
+    }
+
+    /// The stiffness between tool tip and table
+    pub fn total_stiffness(&self) -> Stiffness {
+        let compliance = self
+            .elements
+            .iter()
+            .map(|element| 1. / element.stiffness.value_n_per_m())
+            .sum();
+
+        Stiffness::from_compliance_m_per_n(compliance)
+    }
+
+    /// The elements, weakest first, with their share of the total deflection
+    pub fn ranked(&self) -> Vec<(&Element, f64)> {
+        let total = 1. / self.total_stiffness().value_n_per_m();
+
+        let mut ranked = self
+            .elements
+            .iter()
+            .map(|element| {
+                let share = 1. / element.stiffness.value_n_per_m() / total;
+                (element, share)
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+        ranked
+    }
+}
+
+/// An element of the [`ComplianceChain`]
+pub struct Element {
+    pub name: &'static str,
+    pub source: Source,
+    pub stiffness: Stiffness,
+}
+
+/// Where the stiffness of an [`Element`] comes from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Source {
+    BallScrew,
+    Estimate,
+    Frame,
+    Rails,
+    Tool,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::BallScrew => write!(f, "ball screw"),
+            Source::Estimate => write!(f, "estimate"),
+            Source::Frame => write!(f, "frame model"),
+            Source::Rails => write!(f, "rail catalogue"),
+            Source::Tool => write!(f, "tool deflection"),
+        }
+    }
+}
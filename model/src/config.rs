@@ -0,0 +1,587 @@
+//! Machine configuration files
+//!
+//! The design used to be spread over the code: a rail here, a motor there, a
+//! safety factor somewhere else. Trying out an alternative meant editing all
+//! of those places, and going back meant editing them again. Now a design is a
+//! TOML file in `machines/`, and alternative designs are just more files.
+//!
+//! Loading a file checks everything that can be checked up front, like
+//! whether the parts exist in the catalogues, so the calculations don't have
+//! to deal with nonsense.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    frame::FixedGantryFrame,
+    machine::{
+        axes::Axis,
+        configuration::{Configuration, Kinematics},
+        drivers::StepperDriver,
+        drives::AxisDrive,
+        electronics::Electronics,
+        layout::Layout,
+        motors::StepperMotor,
+        power_supply::PowerSupply,
+        rails::Rail,
+        screws::BallScrew,
+        spindle::{Spindle, SpindleClamp},
+    },
+    optimizer::Constraints,
+    physics::{Diameter, Length, Power, RotationalSpeed},
+    tools::Tool,
+};
+
+/// A complete machine design, as described by a configuration file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MachineConfig {
+    pub name: String,
+    pub configuration: Configuration,
+
+    /// The outer dimensions of the machine, along x, y, and z, in mm
+    pub outer_dimensions: [f64; 3],
+
+    /// The thickness of the base plate and the columns, in mm
+    pub plate_thickness: f64,
+
+    /// The size of the table, along x and y, in mm
+    pub table: [f64; 2],
+
+    /// The construction option of the frame, by its identifier
+    ///
+    /// This picks the section of the columns and gantry beam, and with that,
+    /// the material of the frame. See [`FixedGantryFrame::id`].
+    pub frame: String,
+
+    pub rail: String,
+    pub spindle: SpindleConfig,
+    pub drives: DrivesConfig,
+    pub electronics: ElectronicsConfig,
+
+    /// The tool catalogue, relative to the configuration file
+    ///
+    /// If this is missing, the built-in catalogue is used.
+    pub tools: Option<PathBuf>,
+
+    /// The budget, in €
+    pub budget: f64,
+
+    pub safety_factors: SafetyFactors,
+
+    /// The tools, loaded from [`Self::tools`]
+    #[serde(skip)]
+    tool_catalogue: Option<Vec<ToolConfig>>,
+}
+
+impl MachineConfig {
+    /// Load and validate a configuration file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+
+        let mut config: Self = read_toml(path)?;
+
+        let mut problems = config.validate();
+
+        if let Some(tools) = &config.tools {
+            let tools_path = path.parent().unwrap_or(Path::new("")).join(tools);
+            let catalogue: ToolCatalogue = read_toml(&tools_path)?;
+
+            if catalogue.tools.is_empty() {
+                problems.push(format!(
+                    "tool catalogue `{}` is empty",
+                    tools_path.display()
+                ));
+            }
+            for (i, tool) in catalogue.tools.iter().enumerate() {
+                problems.extend(tool.validate().into_iter().map(|problem| {
+                    format!(
+                        "tool {} in `{}`: {problem}",
+                        i + 1,
+                        tools_path.display()
+                    )
+                }));
+            }
+
+            config.tool_catalogue = Some(catalogue.tools);
+        }
+
+        if !problems.is_empty() {
+            return Err(Error::Invalid {
+                path: path.to_path_buf(),
+                problems,
+            });
+        }
+
+        Ok(config)
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let mut positive = |name: &str, value: f64| {
+            if value <= 0. || !value.is_finite() {
+                problems.push(format!("`{name}` must be positive"));
+            }
+        };
+        for (axis, value) in ["x", "y", "z"].iter().zip(self.outer_dimensions) {
+            positive(&format!("outer_dimensions.{axis}"), value);
+        }
+        positive("plate_thickness", self.plate_thickness);
+        for (axis, value) in ["x", "y"].iter().zip(self.table) {
+            positive(&format!("table.{axis}"), value);
+        }
+        positive("spindle.power_kw", self.spindle.power_kw);
+        positive("spindle.min_rpm", self.spindle.min_rpm);
+        positive("spindle.max_rpm", self.spindle.max_rpm);
+        positive("budget", self.budget);
+        positive(
+            "safety_factors.screw_life_km",
+            self.safety_factors.screw_life_km,
+        );
+
+        // Zero is fine for the controller, if it's powered separately.
+        let controller_power_w = self.electronics.controller_power_w;
+        if !controller_power_w.is_finite() || controller_power_w < 0. {
+            problems.push(
+                "`electronics.controller_power_w` must not be negative"
+                    .to_string(),
+            );
+        }
+
+        let inner_width = self.outer_dimensions[0] - self.plate_thickness * 2.;
+        if self.table[0] > inner_width {
+            problems.push(format!(
+                "table is wider than the space between the columns \
+                ({inner_width} mm)"
+            ));
+        }
+        if self.table[1] > self.outer_dimensions[1] {
+            problems.push("table is deeper than the machine".to_string());
+        }
+
+        if self.spindle.min_rpm >= self.spindle.max_rpm {
+            problems.push(
+                "`spindle.min_rpm` must be less than `spindle.max_rpm`"
+                    .to_string(),
+            );
+        }
+
+        for (name, value) in [
+            ("motor_torque", self.safety_factors.motor_torque),
+            ("rail_static", self.safety_factors.rail_static),
+        ] {
+            if !value.is_finite() || value < 1. {
+                problems.push(format!(
+                    "`safety_factors.{name}` must be at least 1"
+                ));
+            }
+        }
+
+        if !FixedGantryFrame::options()
+            .iter()
+            .any(|frame| frame.id == self.frame)
+        {
+            problems.push(format!("unknown frame `{}`", self.frame));
+        }
+        if !Rail::rails().iter().any(|rail| rail.name == self.rail) {
+            problems.push(format!("unknown rail `{}`", self.rail));
+        }
+        if !PowerSupply::power_supplies().iter().any(|power_supply| {
+            power_supply.name == self.electronics.power_supply
+        }) {
+            problems.push(format!(
+                "unknown power supply `{}`",
+                self.electronics.power_supply
+            ));
+        }
+
+        for (axis, drive) in self.drives.iter() {
+            let mut problem = |problem: String| {
+                problems.push(format!("{axis}-axis drive: {problem}"));
+            };
+
+            if !StepperDriver::drivers()
+                .iter()
+                .any(|driver| driver.name == drive.driver)
+            {
+                problem(format!("unknown driver `{}`", drive.driver));
+            }
+            if !StepperMotor::motors()
+                .iter()
+                .any(|motor| motor.name == drive.motor)
+            {
+                problem(format!("unknown motor `{}`", drive.motor));
+            }
+            if !BallScrew::screws()
+                .iter()
+                .any(|screw| screw.name == drive.screw)
+            {
+                problem(format!("unknown ball screw `{}`", drive.screw));
+            }
+            if !drive.microsteps.is_power_of_two() {
+                problem("`microsteps` must be a power of two".to_string());
+            }
+            if !drive.screw_length.is_finite() || drive.screw_length <= 0. {
+                problem("`screw_length` must be positive".to_string());
+            }
+        }
+
+        problems
+    }
+
+    pub fn layout(&self, spindle_clamp: &SpindleClamp) -> Layout {
+        let mut layout =
+            Layout::new(self.configuration, self.rail(), spindle_clamp);
+        layout.outer = self.outer_dimensions.map(Length::from_value_mm);
+        layout.plate_thickness = Length::from_value_mm(self.plate_thickness);
+        layout.table = self.table.map(Length::from_value_mm);
+        layout
+    }
+
+    pub fn rail(&self) -> Rail {
+        Rail::by_name(&self.rail)
+    }
+
+    pub fn frame(&self) -> FixedGantryFrame {
+        FixedGantryFrame::by_id(&self.frame)
+    }
+
+    pub fn spindle(&self) -> Spindle {
+        Spindle::new(
+            Power::from_value_kw(self.spindle.power_kw),
+            RotationalSpeed::from_value_rpm(self.spindle.min_rpm),
+            RotationalSpeed::from_value_rpm(self.spindle.max_rpm),
+        )
+    }
+
+    pub fn kinematics(&self, spindle_clamp: &SpindleClamp) -> Kinematics {
+        let motor_masses = [&self.drives.x, &self.drives.y, &self.drives.z]
+            .map(|drive| StepperMotor::by_name(&drive.motor).mass);
+        Kinematics::new(
+            &self.layout(spindle_clamp),
+            &self.frame(),
+            motor_masses,
+        )
+    }
+
+    pub fn drives(&self, spindle_clamp: &SpindleClamp) -> Vec<AxisDrive> {
+        let kinematics = self.kinematics(spindle_clamp);
+
+        self.drives
+            .iter()
+            .map(|(axis, drive)| AxisDrive {
+                axis,
+                driver: StepperDriver::by_name(&drive.driver),
+                microsteps: drive.microsteps,
+                motor: StepperMotor::by_name(&drive.motor),
+                screw: BallScrew::by_name(&drive.screw),
+                screw_length: Length::from_value_mm(drive.screw_length),
+                moving_mass: kinematics.axis(axis).moving_mass,
+                safety_factor: self.safety_factors.motor_torque,
+            })
+            .collect()
+    }
+
+    pub fn electronics(&self) -> Electronics {
+        Electronics {
+            power_supply: PowerSupply::by_name(&self.electronics.power_supply),
+            controller_power: Power::from_value_w(
+                self.electronics.controller_power_w,
+            ),
+        }
+    }
+
+    pub fn tools(&self) -> Vec<Tool> {
+        match &self.tool_catalogue {
+            Some(tools) => tools.iter().map(ToolConfig::to_tool).collect(),
+            None => Tool::tools(),
+        }
+    }
+
+    pub fn constraints(&self) -> Constraints {
+        Constraints {
+            rail_safety_factor: self.safety_factors.rail_static,
+            screw_life: Length::from_value_m(
+                self.safety_factors.screw_life_km * 1000.,
+            ),
+            ..Constraints::new()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpindleConfig {
+    pub power_kw: f64,
+
+    /// The speed range, as listed by the sellers of the spindle
+    pub min_rpm: f64,
+    pub max_rpm: f64,
+}
+
+/// The drives of the three axes
+///
+/// Having a field per axis, rather than a list, means every axis is there
+/// exactly once.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DrivesConfig {
+    pub x: DriveConfig,
+    pub y: DriveConfig,
+    pub z: DriveConfig,
+}
+
+impl DrivesConfig {
+    pub fn iter(&self) -> impl Iterator<Item = (Axis, &DriveConfig)> {
+        [(Axis::X, &self.x), (Axis::Y, &self.y), (Axis::Z, &self.z)].into_iter()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DriveConfig {
+    pub driver: String,
+    pub microsteps: u32,
+    pub motor: String,
+    pub screw: String,
+
+    /// The length of the ball screw, in mm
+    pub screw_length: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ElectronicsConfig {
+    pub power_supply: String,
+
+    /// The power draw of the controller board, in W
+    pub controller_power_w: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SafetyFactors {
+    /// The ratio of available to required motor torque
+    pub motor_torque: f64,
+
+    /// The minimum static safety factor of the rail carriages
+    pub rail_static: f64,
+
+    /// The minimum rated life of the ball screws, in km
+    pub screw_life_km: f64,
+}
+
+/// A tool catalogue file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ToolCatalogue {
+    tools: Vec<ToolConfig>,
+}
+
+/// A tool, with all lengths in mm
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ToolConfig {
+    diameter: f64,
+    length_cutting_edge: f64,
+    length_total: f64,
+    num_flutes: f64,
+}
+
+impl ToolConfig {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if !self.diameter.is_finite() || self.diameter <= 0. {
+            problems.push("`diameter` must be positive".to_string());
+        }
+        // That's where the feed table in `Tool::feed_per_tooth` ends.
+        if self.diameter > 12. {
+            problems.push("no feed data for tools above 12 mm".to_string());
+        }
+        if !self.length_cutting_edge.is_finite()
+            || self.length_cutting_edge <= 0.
+        {
+            problems.push("`length_cutting_edge` must be positive".to_string());
+        }
+        if !self.length_total.is_finite()
+            || self.length_total < self.length_cutting_edge
+        {
+            problems.push(
+                "`length_total` must not be less than `length_cutting_edge`"
+                    .to_string(),
+            );
+        }
+        if self.num_flutes < 1. || self.num_flutes.fract() != 0. {
+            problems
+                .push("`num_flutes` must be a positive integer".to_string());
+        }
+
+        problems
+    }
+
+    fn to_tool(&self) -> Tool {
+        Tool {
+            diameter: Diameter::from_length(Length::from_value_mm(
+                self.diameter,
+            )),
+            length_cutting_edge: Length::from_value_mm(
+                self.length_cutting_edge,
+            ),
+            length_total: Length::from_value_mm(self.length_total),
+            num_flutes: self.num_flutes,
+        }
+    }
+}
+
+fn read_toml<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let text = fs::read_to_string(path).map_err(|source| Error::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    toml::from_str(&text).map_err(|source| Error::Parse {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// An error loading a [`MachineConfig`]
+#[derive(Debug)]
+pub enum Error {
+    Read {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    /// The file is well-formed, but describes a machine that can't exist
+    Invalid {
+        path: PathBuf,
+        problems: Vec<String>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Read { path, source } => {
+                write!(f, "Can't read `{}`: {source}", path.display())
+            }
+            Error::Parse { path, source } => {
+                write!(f, "Can't parse `{}`: {source}", path.display())
+            }
+            Error::Invalid { path, problems } => {
+                write!(
+                    f,
+                    "Invalid machine configuration `{}`:",
+                    path.display()
+                )?;
+                for problem in problems {
+                    write!(f, "\n- {problem}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Read { source, .. } => Some(source),
+            Error::Parse { source, .. } => Some(source),
+            Error::Invalid { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, process};
+
+    use super::{Error, MachineConfig};
+
+    #[test]
+    fn shipped_machines_are_valid() {
+        for name in ["fixed-gantry.toml", "moving-gantry.toml"] {
+            if let Err(err) = MachineConfig::load(machines().join(name)) {
+                panic!("{err}");
+            }
+        }
+    }
+
+    #[test]
+    fn bad_values_are_reported() {
+        let mut config: MachineConfig =
+            toml::from_str(&fixed_gantry()).unwrap();
+        assert!(config.validate().is_empty());
+
+        config.safety_factors.motor_torque = f64::NAN;
+        config.spindle.min_rpm = config.spindle.max_rpm;
+        config.drives.z.microsteps = 6;
+
+        assert_eq!(
+            config.validate(),
+            [
+                "`spindle.min_rpm` must be less than `spindle.max_rpm`",
+                "`safety_factors.motor_torque` must be at least 1",
+                "z-axis drive: `microsteps` must be a power of two",
+            ]
+        );
+    }
+
+    #[test]
+    fn non_finite_max_rpm_is_reported() {
+        for max_rpm in [f64::NAN, f64::INFINITY] {
+            let mut config: MachineConfig =
+                toml::from_str(&fixed_gantry()).unwrap();
+            config.spindle.max_rpm = max_rpm;
+
+            assert_eq!(
+                config.validate(),
+                ["`spindle.max_rpm` must be positive"]
+            );
+        }
+    }
+
+    #[test]
+    fn loading_an_invalid_file_fails() {
+        // The identifier that frames used to go by, before they had stable
+        // ones.
+        let text = fixed_gantry().replace(
+            "frame = \"aluminium-plate-150x20\"",
+            "frame = \"aluminium plate 150x20 mm\"",
+        );
+        let path = env::temp_dir()
+            .join(format!("model-{}-invalid.toml", process::id()));
+        fs::write(&path, text).unwrap();
+
+        let result = MachineConfig::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(Error::Invalid { problems, .. }) => assert_eq!(
+                problems,
+                ["unknown frame `aluminium plate 150x20 mm`"]
+            ),
+            Err(err) => panic!("unexpected error: {err}"),
+            Ok(_) => panic!("loaded an invalid file"),
+        }
+    }
+
+    fn machines() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../machines")
+    }
+
+    fn fixed_gantry() -> String {
+        fs::read_to_string(machines().join("fixed-gantry.toml")).unwrap()
+    }
+}
@@ -0,0 +1,185 @@
+//! Prices, vendors, and what it costs to get parts from them
+//!
+//! The budget is 1000 €, and the README has two kinds of sources in mind:
+//! European shops, which are easy but more expensive, and sellers on
+//! AliExpress and eBay, which are cheaper but ship from China. The latter
+//! list prices without import VAT and customs duties, so comparing list prices
+//! alone would be misleading.
+//!
+//! All prices are estimates, from what the vendors listed when I looked. They
+//! change all the time, so don't expect more than a ballpark.
+
+use std::fmt;
+
+/// The price of a part, as listed by a vendor
+#[derive(Clone, Copy, Debug)]
+pub struct Price {
+    pub value: f64,
+    pub currency: Currency,
+    pub vendor: &'static str,
+}
+
+impl Price {
+    pub const fn eur(value: f64, vendor: &'static str) -> Self {
+        Self {
+            value,
+            currency: Currency::Eur,
+            vendor,
+        }
+    }
+
+    pub const fn usd(value: f64, vendor: &'static str) -> Self {
+        Self {
+            value,
+            currency: Currency::Usd,
+            vendor,
+        }
+    }
+
+    /// Scale the price, for parts that are sold by length
+    pub fn scale(self, factor: f64) -> Self {
+        Self {
+            value: self.value * factor,
+            ..self
+        }
+    }
+
+    /// The listed price, converted to €
+    pub fn value_eur(&self) -> f64 {
+        self.value * self.currency.eur_rate()
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.currency {
+            Currency::Eur => write!(f, "{:.2} €", self.value),
+            Currency::Usd => write!(f, "${:.2}", self.value),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Currency {
+    Eur,
+    Usd,
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Currency::Eur => write!(f, "EUR"),
+            Currency::Usd => write!(f, "USD"),
+        }
+    }
+}
+
+impl Currency {
+    /// The exchange rate to €
+    pub fn eur_rate(&self) -> f64 {
+        match self {
+            Currency::Eur => 1.,
+            Currency::Usd => 0.92,
+        }
+    }
+}
+
+/// A shop that parts are ordered from
+#[derive(Debug)]
+pub struct Vendor {
+    pub name: &'static str,
+    pub source: Source,
+
+    /// The shipping cost per order, in €
+    pub shipping: f64,
+}
+
+impl Vendor {
+    pub fn vendors() -> Vec<Self> {
+        // The shops from the README, plus the ones the catalogue prices come
+        // from. Shipping is what an order of a few parts costs to Germany.
+
+        macro_rules! vendors {
+            ($($name:expr, $source:expr, $shipping:expr;)*) => {
+                vec![
+                    $(
+                        Self {
+                            name: $name,
+                            source: $source,
+                            shipping: $shipping,
+                        },
+                    )*
+                ]
+            };
+        }
+
+        vendors!(
+            "Bulk Man 3D", Source::AliExpress, 12.;
+            "Makerbase", Source::AliExpress, 4.;
+            "motor-mall", Source::Eu, 0.;
+            "Reichelt", Source::Eu, 5.95;
+            "Sorotec", Source::Eu, 6.9;
+            "StepperOnline", Source::Eu, 0.;
+            "Zhong Hua Jiang", Source::Ebay, 25.;
+        )
+    }
+
+    /// Find a vendor from the catalogue by name
+    pub fn by_name(name: &str) -> Self {
+        Self::vendors()
+            .into_iter()
+            .find(|vendor| vendor.name == name)
+            .unwrap()
+    }
+}
+
+/// Where a vendor ships from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Source {
+    /// A shop within the EU
+    ///
+    /// That includes Chinese sellers who ship from a European warehouse.
+    /// Prices include VAT, and there are no customs.
+    Eu,
+
+    /// A seller on AliExpress, shipping from China
+    AliExpress,
+
+    /// A seller on eBay, shipping from China
+    Ebay,
+}
+
+impl Source {
+    /// The import VAT and customs duties on an order of the given value, in €
+    ///
+    /// Since 2021, import VAT is due on every order from outside the EU. For
+    /// orders of up to 150 €, AliExpress and eBay collect it at checkout.
+    /// Above that, customs duties are due too. For machine parts, those are
+    /// in the low single-digit percent.
+    pub fn import_charges(&self, order_value: f64) -> f64 {
+        let vat = 0.19;
+        let duty = 0.03;
+
+        match self {
+            Source::Eu => 0.,
+            Source::AliExpress | Source::Ebay => {
+                if order_value > 150. {
+                    let duties = order_value * duty;
+                    duties + (order_value + duties) * vat
+                } else {
+                    order_value * vat
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Eu => write!(f, "EU"),
+            Source::AliExpress => write!(f, "AliExpress"),
+            Source::Ebay => write!(f, "eBay"),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! The parts of a design, and what follows from them
+//!
+//! `cnc` prints what it computes for a design, and [`Analysis`] collects the
+//! same for comparing two designs. Both start from a [`Design`], so they work
+//! with the same parts and can't drift apart.
+//!
+//! [`Analysis`]: crate::analysis::Analysis
+
+use std::error::Error;
+
+use crate::{
+    bom::Bom,
+    compliance::ComplianceChain,
+    config::MachineConfig,
+    dynamics::{StabilityLobes, ToolTipDynamics},
+    frame::FixedGantryFrame,
+    machine::{
+        axes::Axis,
+        configuration::Kinematics,
+        drives::{self, AxisDrive},
+        electronics::Electronics,
+        layout::Layout,
+        limits::AxisLimits,
+        rails::Rail,
+        resolution::StallRisk,
+        spindle::{Spindle, SpindleClamp, SpindleGeometry},
+        stack_up::StackUp,
+    },
+    optimizer::Constraints,
+    physics::{Force, Voltage},
+    tools::Tool,
+    worst_case_force, TangentialCuttingForce,
+};
+
+/// A design, as loaded from its configuration
+pub struct Design {
+    pub spindle: Spindle,
+    pub spindle_geometry: SpindleGeometry,
+
+    /// The spindle holder, which sets width and mass of the clamp
+    pub spindle_clamp: SpindleClamp,
+
+    pub tools: Vec<Tool>,
+    pub rail: Rail,
+    pub frame: FixedGantryFrame,
+    pub electronics: Electronics,
+    pub layout: Layout,
+    pub kinematics: Kinematics,
+    pub drives: Vec<AxisDrive>,
+    pub constraints: Constraints,
+
+    /// The budget, in €
+    pub budget: f64,
+
+    /// The largest tangential cutting force of any tool
+    pub worst_case_force: TangentialCuttingForce,
+
+    /// The tool that causes [`Self::worst_case_force`]
+    pub worst_case_tool: Tool,
+}
+
+impl Design {
+    pub fn new(
+        config: &MachineConfig,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let spindle = config.spindle();
+        let spindle_clamp = SpindleClamp::load()?;
+        let (worst_case_force, worst_case_tool) =
+            worst_case_force(config.tools(), &spindle);
+
+        Ok(Self {
+            spindle_geometry: SpindleGeometry::new(),
+            tools: config.tools(),
+            rail: config.rail(),
+            frame: config.frame(),
+            electronics: config.electronics(),
+            layout: config.layout(&spindle_clamp),
+            kinematics: config.kinematics(&spindle_clamp),
+            drives: config.drives(&spindle_clamp),
+            constraints: config.constraints(),
+            budget: config.budget,
+            worst_case_force,
+            worst_case_tool,
+            spindle,
+            spindle_clamp,
+        })
+    }
+
+    /// The cutting force that the axis drives are sized for
+    ///
+    /// That's the full worst-case cutting force, acting along any of the axes.
+    pub fn cutting_force(&self) -> Force {
+        self.worst_case_force.value()
+    }
+
+    pub fn supply_voltage(&self) -> Voltage {
+        self.electronics.power_supply.voltage
+    }
+
+    /// Indicate whether a tool needs more torque than the spindle has
+    pub fn overloads_spindle(&self, tool: &Tool) -> bool {
+        let (_, tool_torque) = tool.tangential_cutting_force();
+        tool_torque > self.spindle.torque(tool.desired_rpm())
+    }
+
+    pub fn stack_up(&self) -> StackUp {
+        StackUp::new(&self.layout)
+    }
+
+    /// The limits of each axis, under the cutting force
+    pub fn limits(&self) -> Vec<AxisLimits> {
+        self.drives
+            .iter()
+            .map(|drive| {
+                AxisLimits::new(
+                    drive,
+                    self.cutting_force(),
+                    self.supply_voltage(),
+                )
+            })
+            .collect()
+    }
+
+    /// The stall risk of a drive, for each tool
+    pub fn stall_risks(&self, drive: &AxisDrive) -> Vec<StallRisk<'_>> {
+        StallRisk::for_tools(
+            drive,
+            &self.tools,
+            &self.spindle,
+            drives::design_acceleration(),
+            self.supply_voltage(),
+        )
+    }
+
+    pub fn compliance(&self, direction: Axis, tool: &Tool) -> ComplianceChain {
+        ComplianceChain::new(
+            direction,
+            tool,
+            &self.rail,
+            &self.frame,
+            &self.drives,
+        )
+    }
+
+    /// The stability against chatter, when cutting with a tool
+    ///
+    /// The gantry is weaker across its span, so that's where chatter will
+    /// start. What vibrates with the structure is what the x-axis moves.
+    pub fn chatter(&self, tool: &Tool) -> (ToolTipDynamics, StabilityLobes) {
+        let dynamics = ToolTipDynamics::new(
+            &self.compliance(Axis::Y, tool),
+            tool,
+            self.kinematics.axis(Axis::X).moving_mass,
+        );
+        let lobes = StabilityLobes::new(&dynamics, tool, &self.spindle);
+        (dynamics, lobes)
+    }
+
+    pub fn bom(&self) -> Bom {
+        Bom::new(
+            &self.layout,
+            &self.drives,
+            &self.spindle,
+            &self.spindle_geometry,
+            &self.electronics,
+        )
+    }
+}
@@ -0,0 +1,273 @@
+//! Dynamics of the tool tip, and chatter
+//!
+//! Chatter is a self-excited vibration: The tool vibrates, which leaves a wavy
+//! surface, which modulates the chip thickness on the next pass, which makes
+//! the tool vibrate. Whether that grows or dies down depends on the depth of
+//! cut, the spindle speed, and the dynamics of the structure at the tool tip.
+//!
+//! This uses the classic single-direction model (Tlusty), with the tool-tip
+//! structure reduced to two modes: The machine structure behind the collet,
+//! and the tool itself. That's very rough, and can only really be checked
+//! with a tap test on the real machine, but it should show which tools are
+//! going to be trouble, and which spindle speeds to try first.
+
+use std::f64::consts::PI;
+
+use crate::{
+    compliance::{ComplianceChain, Source},
+    machine::spindle::Spindle,
+    physics::{Frequency, Length, Mass, RotationalSpeed, Stiffness},
+    tools::Tool,
+};
+
+/// A vibration mode, reduced to a single-degree-of-freedom system
+#[derive(Clone, Copy, Debug)]
+pub struct Mode {
+    pub natural_frequency: Frequency,
+    pub stiffness: Stiffness,
+    pub damping_ratio: f64,
+}
+
+impl Mode {
+    fn from_mass(stiffness: Stiffness, mass: Mass, damping_ratio: f64) -> Self {
+        let omega = (stiffness.value_n_per_m() / mass.value_kg()).sqrt();
+
+        Self {
+            natural_frequency: Frequency::from_value_hz(omega / (2. * PI)),
+            stiffness,
+            damping_ratio,
+        }
+    }
+
+    /// The dynamic stiffness at resonance
+    ///
+    /// This is what chatter depends on. A stiff mode with little damping can
+    /// be worse than a soft mode with a lot of it.
+    pub fn dynamic_stiffness(&self) -> Stiffness {
+        Stiffness::from_value_n_per_m(
+            2. * self.damping_ratio * self.stiffness.value_n_per_m(),
+        )
+    }
+
+    /// The frequency response function, as real and imaginary part in m/N
+    fn frf(&self, frequency: f64) -> (f64, f64) {
+        let r = frequency / self.natural_frequency.value_hz();
+        let k = self.stiffness.value_n_per_m();
+        let a = 1. - r.powi(2);
+        let b = 2. * self.damping_ratio * r;
+        let denominator = k * (a.powi(2) + b.powi(2));
+
+        (a / denominator, -b / denominator)
+    }
+}
+
+/// The dynamics at the tool tip
+pub struct ToolTipDynamics {
+    pub structure: Mode,
+    pub tool: Mode,
+}
+
+impl ToolTipDynamics {
+    /// Derive the dynamics from the compliance chain of a tool
+    ///
+    /// `moving_mass` is the mass that vibrates with the machine structure.
+    pub fn new(
+        chain: &ComplianceChain,
+        tool: &Tool,
+        moving_mass: Mass,
+    ) -> Self {
+        // Everything but the tool vibrates as one mode, as the machine's
+        // mass is much larger than the tool's.
+        let structure_compliance = chain
+            .elements
+            .iter()
+            .filter(|element| element.source != Source::Tool)
+            .map(|element| 1. / element.stiffness.value_n_per_m())
+            .sum();
+
+        // Bolted aluminium structures are usually quoted at 2-5% damping.
+        // Let's take the middle.
+        let structure = Mode::from_mass(
+            Stiffness::from_compliance_m_per_n(structure_compliance),
+            moving_mass,
+            0.03,
+        );
+
+        // The tool is a cantilever. For the first mode, 33/140 of its mass
+        // acts at the tip. Let's use the shank diameter for that, which
+        // overestimates the mass a bit. Solid carbide has hardly any damping.
+        let density_carbide = 14_500.; // kg/m³
+        let d = tool.shank_diameter().to_length().value_m();
+        let l = tool.stick_out().value_m();
+        let tool_mass = density_carbide * PI * d.powi(2) / 4. * l;
+        let tool = Mode::from_mass(
+            tool.stiffness(),
+            Mass::from_value_kg(tool_mass * 33. / 140.),
+            0.01,
+        );
+
+        Self { structure, tool }
+    }
+
+    /// The mode that's most likely to chatter
+    pub fn dominant_mode(&self) -> Mode {
+        if self.tool.dynamic_stiffness() < self.structure.dynamic_stiffness() {
+            self.tool
+        } else {
+            self.structure
+        }
+    }
+
+    fn frf(&self, frequency: f64) -> (f64, f64) {
+        // The modes are in series, so their responses add up.
+        let (re_structure, im_structure) = self.structure.frf(frequency);
+        let (re_tool, im_tool) = self.tool.frf(frequency);
+
+        (re_structure + re_tool, im_structure + im_tool)
+    }
+}
+
+/// The stability lobe diagram for a tool
+///
+/// This is the max axial depth of cut that doesn't chatter, over the speed
+/// range of the spindle.
+pub struct StabilityLobes {
+    /// The depth of cut that is stable at any spindle speed
+    pub critical_depth: Length,
+
+    /// The max stable depth of cut, sampled over the spindle's speed range
+    pub limits: Vec<(RotationalSpeed, Length)>,
+}
+
+impl StabilityLobes {
+    /// The step between samples in [`Self::limits`]
+    const SPEED_STEP_RPM: f64 = 100.;
+
+    pub fn new(
+        dynamics: &ToolTipDynamics,
+        tool: &Tool,
+        spindle: &Spindle,
+    ) -> Self {
+        // Specific cutting force for aluminium alloys, as given by Altintas.
+        let cutting_coefficient = 800_000_000.; // Pascal
+
+        // The average number of teeth in cut, for the side milling case at 25%
+        // radial depth of cut (see `Tool::tangential_cutting_force`).
+        let engagement_angle = (1. - 2. * 0.25_f64).acos();
+        let teeth_in_cut = tool.num_flutes * engagement_angle / (2. * PI);
+
+        let min = spindle.min_speed.value_rpm();
+        let max = spindle.max_speed.value_rpm();
+        let num_samples = ((max - min) / Self::SPEED_STEP_RPM) as usize + 1;
+
+        let mut limits = vec![f64::INFINITY; num_samples];
+        let mut critical_depth = f64::INFINITY;
+
+        // The speed and depth at the previous chatter frequency, for each
+        // lobe. The frequencies below are sampled, so there would be gaps
+        // between the speeds they map to, unless we fill those in.
+        let mut previous: Vec<Option<(f64, f64)>> = Vec::new();
+
+        // Chatter happens slightly above a natural frequency, so let's look at
+        // everything up to well beyond the highest one.
+        let max_frequency = 3.
+            * dynamics
+                .structure
+                .natural_frequency
+                .value_hz()
+                .max(dynamics.tool.natural_frequency.value_hz());
+        let mut chatter_frequency = 1.;
+        while chatter_frequency < max_frequency {
+            let (re, im) = dynamics.frf(chatter_frequency);
+            chatter_frequency *= 1.001;
+
+            // Only frequencies with a negative real part can chatter.
+            if re >= 0. {
+                previous.clear();
+                continue;
+            }
+
+            let depth = -1. / (2. * cutting_coefficient * teeth_in_cut * re);
+            critical_depth = critical_depth.min(depth);
+
+            // The phase between inner and outer modulation, and from that, the
+            // spindle speed for each lobe.
+            let epsilon = 3. * PI + 2. * im.atan2(re);
+            for lobe in 0.. {
+                let tooth_passing_frequency =
+                    chatter_frequency / (lobe as f64 + epsilon / (2. * PI));
+                let rpm = tooth_passing_frequency * 60. / tool.num_flutes;
+
+                if lobe == previous.len() {
+                    previous.push(None);
+                }
+                let (low, high, depth) = match previous[lobe] {
+                    Some((prev_rpm, prev_depth)) => (
+                        prev_rpm.min(rpm),
+                        prev_rpm.max(rpm),
+                        prev_depth.min(depth),
+                    ),
+                    None => (rpm, rpm, depth),
+                };
+                previous[lobe] = Some((rpm, depth));
+
+                // Higher lobes are at lower speeds.
+                if high < min {
+                    break;
+                }
+                if low > max {
+                    continue;
+                }
+
+                let first = ((low.max(min) - min) / Self::SPEED_STEP_RPM).ceil()
+                    as usize;
+                let last = ((high.min(max) - min) / Self::SPEED_STEP_RPM)
+                    .floor() as usize;
+                for limit in limits.iter_mut().take(last + 1).skip(first) {
+                    *limit = limit.min(depth);
+                }
+            }
+        }
+
+        // Where no lobe reaches, the cut is stable at any depth. The tool
+        // can't cut deeper than its cutting edge though.
+        let max_depth = tool.length_cutting_edge.value_m();
+        let limits = limits
+            .into_iter()
+            .enumerate()
+            .map(|(i, depth)| {
+                let speed = RotationalSpeed::from_value_rpm(
+                    min + i as f64 * Self::SPEED_STEP_RPM,
+                );
+                (speed, Length::from_value_m(depth.min(max_depth)))
+            })
+            .collect();
+
+        Self {
+            critical_depth: Length::from_value_m(critical_depth),
+            limits,
+        }
+    }
+
+    /// The max stable depth of cut at the given speed
+    pub fn limit_at(&self, speed: RotationalSpeed) -> Length {
+        self.limits
+            .iter()
+            .min_by(|a, b| {
+                let a = (a.0.value_rpm() - speed.value_rpm()).abs();
+                let b = (b.0.value_rpm() - speed.value_rpm()).abs();
+                a.partial_cmp(&b).unwrap()
+            })
+            .unwrap()
+            .1
+    }
+
+    /// The spindle speed that allows the deepest stable cut
+    pub fn best_speed(&self) -> (RotationalSpeed, Length) {
+        *self
+            .limits
+            .iter()
+            .reduce(|a, b| if b.1.value_m() > a.1.value_m() { b } else { a })
+            .unwrap()
+    }
+}
@@ -0,0 +1,525 @@
+//! A small finite element solver for 3D frames
+//!
+//! Supports linear-elastic beam elements (Timoshenko, so shear deformation of
+//! short, deep members is accounted for), springs between nodes (to model
+//! joints), fixed supports, and point loads. The system is small enough that
+//! a dense solver is perfectly fine.
+
+use crate::{
+    machine::axes::Axis,
+    physics::{Force, Length, Pressure},
+};
+
+use super::sections::SectionProperties;
+
+/// The number of degrees of freedom per node
+///
+/// Translation along x, y, z, followed by rotation around x, y, z.
+const DOFS: usize = 6;
+
+/// A frame model
+#[derive(Default)]
+pub struct Model {
+    nodes: Vec<[f64; 3]>,
+    beams: Vec<Beam>,
+    springs: Vec<Spring>,
+    supports: Vec<NodeId>,
+    loads: Vec<(NodeId, Axis, Force)>,
+}
+
+impl Model {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a node at the given position
+    pub fn add_node(&mut self, position: [Length; 3]) -> NodeId {
+        self.nodes.push(position.map(|coord| coord.value_m()));
+        NodeId(self.nodes.len() - 1)
+    }
+
+    /// Add a beam element between two nodes
+    ///
+    /// `strong_direction` must be perpendicular to the beam. The strong axis
+    /// of the section resists bending in that direction.
+    pub fn add_beam(
+        &mut self,
+        nodes: [NodeId; 2],
+        section: SectionProperties,
+        elastic_modulus: Pressure,
+        shear_modulus: Pressure,
+        strong_direction: Axis,
+    ) {
+        self.beams.push(Beam {
+            nodes,
+            section,
+            elastic_modulus,
+            shear_modulus,
+            strong_direction,
+        });
+    }
+
+    /// Add a spring between two nodes
+    ///
+    /// The translational degrees of freedom are coupled rigidly, the rotational
+    /// ones by the given stiffness (in Nm/rad).
+    pub fn add_spring(
+        &mut self,
+        nodes: [NodeId; 2],
+        rotational_stiffness: f64,
+    ) {
+        self.springs.push(Spring {
+            nodes,
+            rotational_stiffness,
+        });
+    }
+
+    /// Fix all degrees of freedom of a node
+    pub fn fix(&mut self, node: NodeId) {
+        self.supports.push(node);
+    }
+
+    /// Apply a force to a node
+    pub fn add_load(&mut self, node: NodeId, direction: Axis, force: Force) {
+        self.loads.push((node, direction, force));
+    }
+
+    /// Solve for displacements and reactions
+    ///
+    /// Panics, if the model is not sufficiently supported.
+    pub fn solve(&self) -> Solution {
+        let n = self.nodes.len() * DOFS;
+        let mut k = vec![vec![0.; n]; n];
+
+        for beam in &self.beams {
+            let a = self.nodes[beam.nodes[0].0];
+            let b = self.nodes[beam.nodes[1].0];
+            let k_e = beam.global_stiffness(a, b);
+
+            let dofs = dofs(beam.nodes);
+            for (i, &dof_i) in dofs.iter().enumerate() {
+                for (j, &dof_j) in dofs.iter().enumerate() {
+                    k[dof_i][dof_j] += k_e[i][j];
+                }
+            }
+        }
+
+        // Rigid couplings are modeled as very stiff springs. That's much
+        // simpler than eliminating degrees of freedom, and accurate enough,
+        // as long as it's a few orders of magnitude stiffer than everything
+        // else.
+        let rigid = k
+            .iter()
+            .enumerate()
+            .map(|(i, row)| row[i])
+            .fold(0., f64::max)
+            * 1e6;
+        for spring in &self.springs {
+            for dof in 0..DOFS {
+                let stiffness = if dof < 3 {
+                    rigid
+                } else {
+                    spring.rotational_stiffness
+                };
+
+                let i = spring.nodes[0].0 * DOFS + dof;
+                let j = spring.nodes[1].0 * DOFS + dof;
+
+                k[i][i] += stiffness;
+                k[j][j] += stiffness;
+                k[i][j] -= stiffness;
+                k[j][i] -= stiffness;
+            }
+        }
+
+        let mut f = vec![0.; n];
+        for &(node, direction, force) in &self.loads {
+            f[node.0 * DOFS + axis_index(direction)] += force.value_n();
+        }
+
+        let fixed = self
+            .supports
+            .iter()
+            .flat_map(|node| (0..DOFS).map(move |dof| node.0 * DOFS + dof))
+            .collect::<Vec<_>>();
+        let free = (0..n).filter(|i| !fixed.contains(i)).collect::<Vec<_>>();
+
+        let k_ff = free
+            .iter()
+            .map(|&i| free.iter().map(|&j| k[i][j]).collect())
+            .collect();
+        let f_f = free.iter().map(|&i| f[i]).collect();
+        let u_f = solve_linear_system(k_ff, f_f);
+
+        let mut displacements = vec![0.; n];
+        for (&i, u) in free.iter().zip(u_f) {
+            displacements[i] = u;
+        }
+
+        // Reactions are whatever the supports need to provide, to keep the
+        // supported nodes in equilibrium.
+        let reactions = (0..n)
+            .map(|i| {
+                if fixed.contains(&i) {
+                    let internal = k[i]
+                        .iter()
+                        .zip(&displacements)
+                        .map(|(k, u)| k * u)
+                        .sum::<f64>();
+                    internal - f[i]
+                } else {
+                    0.
+                }
+            })
+            .collect();
+
+        Solution {
+            displacements,
+            reactions,
+        }
+    }
+}
+
+/// Identifies a node in a [`Model`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NodeId(usize);
+
+/// The solution of a [`Model`]
+pub struct Solution {
+    displacements: Vec<f64>,
+    reactions: Vec<f64>,
+}
+
+impl Solution {
+    /// The displacement of a node in the given direction
+    pub fn displacement(&self, node: NodeId, direction: Axis) -> Length {
+        Length::from_value_m(
+            self.displacements[node.0 * DOFS + axis_index(direction)],
+        )
+    }
+
+    /// The reaction force at a supported node in the given direction
+    pub fn reaction(&self, node: NodeId, direction: Axis) -> Force {
+        Force::from_value_n(
+            self.reactions[node.0 * DOFS + axis_index(direction)],
+        )
+    }
+}
+
+struct Beam {
+    nodes: [NodeId; 2],
+    section: SectionProperties,
+    elastic_modulus: Pressure,
+    shear_modulus: Pressure,
+    strong_direction: Axis,
+}
+
+impl Beam {
+    /// The element stiffness matrix, in global coordinates
+    fn global_stiffness(&self, a: [f64; 3], b: [f64; 3]) -> Matrix12 {
+        let axis = sub(b, a);
+        let l = norm(axis);
+
+        // Local coordinate system: x along the beam, y in the strong
+        // direction, z completing the right-handed system.
+        let x = scale(axis, 1. / l);
+        let y = unit_vector(self.strong_direction);
+        let z = cross(x, y);
+        assert!(
+            norm(z) > 0.99,
+            "Strong direction of beam must be perpendicular to it"
+        );
+        let rotation = [x, y, z];
+
+        let k_local = self.local_stiffness(l);
+
+        // K_global = Tᵀ · K_local · T, with T being the block-diagonal
+        // matrix made from four copies of the rotation matrix.
+        let mut t = [[0.; 12]; 12];
+        for block in 0..4 {
+            for i in 0..3 {
+                for j in 0..3 {
+                    t[block * 3 + i][block * 3 + j] = rotation[i][j];
+                }
+            }
+        }
+
+        let mut k_t = [[0.; 12]; 12];
+        for i in 0..12 {
+            for j in 0..12 {
+                k_t[i][j] = (0..12).map(|m| k_local[i][m] * t[m][j]).sum();
+            }
+        }
+
+        let mut k_global = [[0.; 12]; 12];
+        for i in 0..12 {
+            for j in 0..12 {
+                k_global[i][j] = (0..12).map(|m| t[m][i] * k_t[m][j]).sum();
+            }
+        }
+
+        k_global
+    }
+
+    /// The element stiffness matrix, in local coordinates
+    fn local_stiffness(&self, l: f64) -> Matrix12 {
+        let e = self.elastic_modulus.value_pa();
+        let g = self.shear_modulus.value_pa();
+        let a = self.section.area.value_m2();
+        let a_s = self.section.shear_area.value_m2();
+        let j = self.section.torsion_constant.value_m4();
+
+        // Displacement along local y (the strong direction) means bending
+        // around local z, and vice versa.
+        let i_z = self.section.i_strong.value_m4();
+        let i_y = self.section.i_weak.value_m4();
+
+        let mut k = [[0.; 12]; 12];
+
+        let axial = e * a / l;
+        k[0][0] = axial;
+        k[0][6] = -axial;
+        k[6][6] = axial;
+
+        let torsion = g * j / l;
+        k[3][3] = torsion;
+        k[3][9] = -torsion;
+        k[9][9] = torsion;
+
+        // Bending in local y. Degrees of freedom: v1, θz1, v2, θz2.
+        let phi = 12. * e * i_z / (g * a_s * l.powi(2));
+        let c = e * i_z / (l.powi(3) * (1. + phi));
+        k[1][1] = 12. * c;
+        k[1][5] = 6. * l * c;
+        k[1][7] = -12. * c;
+        k[1][11] = 6. * l * c;
+        k[5][5] = (4. + phi) * l.powi(2) * c;
+        k[5][7] = -6. * l * c;
+        k[5][11] = (2. - phi) * l.powi(2) * c;
+        k[7][7] = 12. * c;
+        k[7][11] = -6. * l * c;
+        k[11][11] = (4. + phi) * l.powi(2) * c;
+
+        // Bending in local z. Degrees of freedom: w1, θy1, w2, θy2. Signs
+        // differ, as positive rotation around y moves in negative z.
+        let phi = 12. * e * i_y / (g * a_s * l.powi(2));
+        let c = e * i_y / (l.powi(3) * (1. + phi));
+        k[2][2] = 12. * c;
+        k[2][4] = -6. * l * c;
+        k[2][8] = -12. * c;
+        k[2][10] = -6. * l * c;
+        k[4][4] = (4. + phi) * l.powi(2) * c;
+        k[4][8] = 6. * l * c;
+        k[4][10] = (2. - phi) * l.powi(2) * c;
+        k[8][8] = 12. * c;
+        k[8][10] = 6. * l * c;
+        k[10][10] = (4. + phi) * l.powi(2) * c;
+
+        let upper = k;
+        for (i, row) in k.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate().take(i) {
+                *value = upper[j][i];
+            }
+        }
+
+        k
+    }
+}
+
+struct Spring {
+    nodes: [NodeId; 2],
+    rotational_stiffness: f64,
+}
+
+type Matrix12 = [[f64; 12]; 12];
+
+fn dofs(nodes: [NodeId; 2]) -> [usize; 12] {
+    let mut dofs = [0; 12];
+    for (i, node) in nodes.iter().enumerate() {
+        for dof in 0..DOFS {
+            dofs[i * DOFS + dof] = node.0 * DOFS + dof;
+        }
+    }
+    dofs
+}
+
+fn axis_index(axis: Axis) -> usize {
+    match axis {
+        Axis::X => 0,
+        Axis::Y => 1,
+        Axis::Z => 2,
+    }
+}
+
+fn unit_vector(axis: Axis) -> [f64; 3] {
+    let mut v = [0.; 3];
+    v[axis_index(axis)] = 1.;
+    v
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(v: [f64; 3], s: f64) -> [f64; 3] {
+    v.map(|c| c * s)
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    (v[0].powi(2) + v[1].powi(2) + v[2].powi(2)).sqrt()
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Solve `a · x = b` using Gaussian elimination with partial pivoting
+fn solve_linear_system(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .unwrap();
+        assert!(
+            a[pivot][col].abs() > 1e-12,
+            "Singular stiffness matrix; is the model supported?"
+        );
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        for row in col + 1..n {
+            let factor = a[row][col] / a[col][col];
+            if factor == 0. {
+                continue;
+            }
+            let (upper, lower) = a.split_at_mut(row);
+            for (value, pivot_value) in
+                lower[0][col..].iter_mut().zip(&upper[col][col..])
+            {
+                *value -= factor * pivot_value;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.; n];
+    for row in (0..n).rev() {
+        let sum = (row + 1..n).map(|k| a[row][k] * x[k]).sum::<f64>();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        frame::sections::SectionProperties,
+        machine::axes::Axis,
+        physics::{Area, Force, Length, Pressure, SecondMomentOfArea},
+    };
+
+    use super::Model;
+
+    const LENGTH_M: f64 = 0.5;
+    const LOAD_N: f64 = 100.;
+
+    const AREA_M2: f64 = 1e-3;
+    const SHEAR_AREA_M2: f64 = 5e-4;
+    const I_STRONG_M4: f64 = 1e-7;
+    const I_WEAK_M4: f64 = 5e-8;
+
+    const E_PA: f64 = 70e9;
+    const G_PA: f64 = 26e9;
+
+    #[test]
+    fn cantilever_tip_deflection_matches_closed_form() {
+        // Bending plus shear deformation, for a point load at the tip.
+        let bending = |i_m4| LOAD_N * LENGTH_M.powi(3) / (3. * E_PA * i_m4);
+        let shear = LOAD_N * LENGTH_M / (G_PA * SHEAR_AREA_M2);
+
+        let strong = cantilever(1, Axis::Y);
+        assert_close(strong, bending(I_STRONG_M4) + shear);
+
+        let weak = cantilever(1, Axis::Z);
+        assert_close(weak, bending(I_WEAK_M4) + shear);
+
+        let axial = cantilever(1, Axis::X);
+        assert_close(axial, LOAD_N * LENGTH_M / (E_PA * AREA_M2));
+    }
+
+    #[test]
+    fn cantilever_result_does_not_depend_on_subdivision() {
+        for direction in [Axis::X, Axis::Y, Axis::Z] {
+            assert_close(cantilever(4, direction), cantilever(1, direction));
+        }
+    }
+
+    #[test]
+    fn support_reacts_the_load() {
+        let mut model = Model::new();
+        let root = model.add_node(point(0.));
+        let tip = model.add_node(point(LENGTH_M));
+        model.add_beam([root, tip], section(), e(), g(), Axis::Y);
+        model.fix(root);
+        model.add_load(tip, Axis::Y, Force::from_value_n(LOAD_N));
+
+        let reaction = model.solve().reaction(root, Axis::Y).value_n();
+        assert!((reaction + LOAD_N).abs() < 1e-6);
+    }
+
+    /// Tip displacement of a cantilever along x, loaded in `direction`
+    ///
+    /// The beam is split into `elements` beam elements, with its strong axis
+    /// resisting bending in y.
+    fn cantilever(elements: usize, direction: Axis) -> f64 {
+        let mut model = Model::new();
+        let nodes = (0..=elements)
+            .map(|i| {
+                model.add_node(point(LENGTH_M * i as f64 / elements as f64))
+            })
+            .collect::<Vec<_>>();
+        for pair in nodes.windows(2) {
+            model.add_beam([pair[0], pair[1]], section(), e(), g(), Axis::Y);
+        }
+        model.fix(nodes[0]);
+
+        let tip = nodes[elements];
+        model.add_load(tip, direction, Force::from_value_n(LOAD_N));
+        model.solve().displacement(tip, direction).value_m()
+    }
+
+    fn point(x_m: f64) -> [Length; 3] {
+        [x_m, 0., 0.].map(Length::from_value_m)
+    }
+
+    fn section() -> SectionProperties {
+        SectionProperties {
+            area: Area::from_value_m2(AREA_M2),
+            shear_area: Area::from_value_m2(SHEAR_AREA_M2),
+            i_strong: SecondMomentOfArea::from_value_m4(I_STRONG_M4),
+            i_weak: SecondMomentOfArea::from_value_m4(I_WEAK_M4),
+            torsion_constant: SecondMomentOfArea::from_value_m4(1e-7),
+        }
+    }
+
+    fn e() -> Pressure {
+        Pressure::from_value_pa(E_PA)
+    }
+
+    fn g() -> Pressure {
+        Pressure::from_value_pa(G_PA)
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            ((actual - expected) / expected).abs() < 1e-6,
+            "{actual} != {expected}"
+        );
+    }
+}
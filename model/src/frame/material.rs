@@ -0,0 +1,36 @@
+use crate::physics::{Mass, Pressure, Volume};
+
+/// A structural material
+#[derive(Clone, Copy, Debug)]
+pub enum Material {
+    Aluminium,
+    Steel,
+}
+
+impl Material {
+    /// Young's modulus
+    pub fn elastic_modulus(&self) -> Pressure {
+        match self {
+            Material::Aluminium => Pressure::from_value_gpa(70.),
+            Material::Steel => Pressure::from_value_gpa(210.),
+        }
+    }
+
+    /// Shear modulus
+    pub fn shear_modulus(&self) -> Pressure {
+        match self {
+            Material::Aluminium => Pressure::from_value_gpa(26.),
+            Material::Steel => Pressure::from_value_gpa(81.),
+        }
+    }
+
+    /// The mass of the given volume of material
+    pub fn mass(&self, volume: Volume) -> Mass {
+        let density = match self {
+            Material::Aluminium => 2700.,
+            Material::Steel => 7850.,
+        };
+
+        Mass::from_value_kg(density * volume.value_m3())
+    }
+}
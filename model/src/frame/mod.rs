@@ -0,0 +1,372 @@
+//! Stiffness of the machine frame
+//!
+//! This compares the construction options from the README (aluminium plates
+//! vs aluminium extrusion, plus steel tube as a reference), using the usual
+//! hand formulas for beams. It's a rough model, but it should be good enough
+//! to show which option is in a different league.
+
+pub mod fem;
+pub mod material;
+pub mod sections;
+
+use crate::{
+    machine::axes::Axis,
+    physics::{Force, Length, Mass, RotationalStiffness, Stiffness, Volume},
+};
+
+use self::{
+    fem::Model,
+    material::Material,
+    sections::{Profile, Section},
+};
+
+/// The frame of the fixed-gantry layout
+///
+/// Two columns stand on the base, and the gantry beam spans the distance
+/// between them. The x-axis is mounted on the gantry beam, and the tool tip
+/// hangs below it.
+pub struct FixedGantryFrame {
+    /// The identifier that configuration files use to pick this option
+    ///
+    /// Unlike the name, it's unique, and unlike the displayed section, it
+    /// doesn't change with formatting.
+    pub id: &'static str,
+
+    pub name: &'static str,
+    pub columns: Member,
+    pub gantry: Member,
+    pub joints: Joint,
+    pub column_height: Length,
+    pub gantry_span: Length,
+
+    /// The vertical distance from the gantry beam to the tool tip
+    pub tool_offset: Length,
+}
+
+impl FixedGantryFrame {
+    /// The construction options to compare
+    pub fn options() -> Vec<Self> {
+        // These are rough dimensions, based on the 40x40x40 cm³ target.
+        let column_height = Length::from_value_mm(300.);
+        let gantry_span = Length::from_value_mm(400.);
+        let tool_offset = Length::from_value_mm(200.);
+
+        let mut options = vec![
+            Self {
+                id: "aluminium-plate-150x20",
+                name: "aluminium plates",
+                columns: Member {
+                    section: Section::Plate {
+                        width: Length::from_value_mm(150.),
+                        thickness: Length::from_value_mm(20.),
+                    },
+                    strong_direction: Axis::Y,
+                },
+                gantry: Member {
+                    section: Section::Plate {
+                        width: Length::from_value_mm(150.),
+                        thickness: Length::from_value_mm(20.),
+                    },
+                    strong_direction: Axis::Z,
+                },
+                joints: Joint {
+                    name: "bolted plates",
+                    rotational_stiffness:
+                        RotationalStiffness::from_value_nm_per_rad(500_000.),
+                },
+                column_height,
+                gantry_span,
+                tool_offset,
+            },
+            Self {
+                id: "steel-tube-60x60x4",
+                name: "steel tube",
+                columns: Member {
+                    section: Section::SteelTube {
+                        width: Length::from_value_mm(60.),
+                        wall_thickness: Length::from_value_mm(4.),
+                    },
+                    strong_direction: Axis::Y,
+                },
+                gantry: Member {
+                    section: Section::SteelTube {
+                        width: Length::from_value_mm(60.),
+                        wall_thickness: Length::from_value_mm(4.),
+                    },
+                    strong_direction: Axis::Y,
+                },
+                joints: Joint {
+                    name: "bolted through",
+                    rotational_stiffness:
+                        RotationalStiffness::from_value_nm_per_rad(1_000_000.),
+                },
+                column_height,
+                gantry_span,
+                tool_offset,
+            },
+        ];
+
+        for profile in Profile::profiles() {
+            options.push(Self {
+                id: profile.id(),
+                name: "aluminium extrusion",
+                columns: Member {
+                    section: Section::Extrusion(profile),
+                    strong_direction: Axis::Y,
+                },
+                gantry: Member {
+                    section: Section::Extrusion(profile),
+                    strong_direction: Axis::Y,
+                },
+                joints: Joint {
+                    name: "angle brackets",
+                    rotational_stiffness:
+                        RotationalStiffness::from_value_nm_per_rad(20_000.),
+                },
+                column_height,
+                gantry_span,
+                tool_offset,
+            });
+        }
+
+        options
+    }
+
+    /// Find a construction option by its identifier
+    pub fn by_id(id: &str) -> Self {
+        Self::options()
+            .into_iter()
+            .find(|frame| frame.id == id)
+            .unwrap()
+    }
+
+    /// The stiffness at the tool tip, against a force in the given direction
+    pub fn stiffness(&self, direction: Axis) -> Stiffness {
+        Stiffness::from_compliance_m_per_n(self.compliance(direction).total())
+    }
+
+    /// The compliance at the tool tip, split up by part of the frame
+    pub fn compliance(&self, direction: Axis) -> FrameCompliance {
+        match direction {
+            Axis::X => self.compliance_x(),
+            Axis::Y => self.compliance_y(),
+            // The columns are loaded in compression, and the gantry beam
+            // bends in its strong direction. Neither is going to matter
+            // compared to the rest of the machine.
+            Axis::Z => FrameCompliance {
+                columns: 0.,
+                gantry: 0.,
+                joints: 0.,
+            },
+        }
+    }
+
+    /// Solve a finite element model of the frame, loaded at the tool tip
+    ///
+    /// This doesn't need the simplifying assumptions of [`Self::stiffness`],
+    /// so it's more accurate for the combined gantry and columns structure.
+    pub fn solve(&self, direction: Axis, force: Force) -> FrameSolution {
+        let h = self.column_height;
+        let half_span = self.gantry_span / 2.;
+        let zero = Length::from_value_m(0.);
+        let k = self.joints.rotational_stiffness.value_nm_per_rad();
+
+        let mut model = Model::new();
+
+        let mut bases = Vec::new();
+        let mut gantry_ends = Vec::new();
+        for x in [-half_span.value_m(), half_span.value_m()] {
+            let x = Length::from_value_m(x);
+
+            let ground = model.add_node([x, zero, zero]);
+            let base = model.add_node([x, zero, zero]);
+            let top = model.add_node([x, zero, h]);
+            let gantry_end = model.add_node([x, zero, h]);
+
+            model.fix(ground);
+            model.add_spring([ground, base], k);
+            self.columns.add_to(&mut model, [base, top]);
+            model.add_spring([top, gantry_end], k);
+
+            bases.push(ground);
+            gantry_ends.push(gantry_end);
+        }
+
+        let center = model.add_node([zero, zero, h]);
+        self.gantry.add_to(&mut model, [gantry_ends[0], center]);
+        self.gantry.add_to(&mut model, [center, gantry_ends[1]]);
+
+        // Everything between gantry and tool tip (x- and z-axis, spindle,
+        // tool) is not part of the frame. Let's make it effectively rigid.
+        let tool_tip = model.add_node([
+            zero,
+            zero,
+            Length::from_value_m(h.value_m() - self.tool_offset.value_m()),
+        ]);
+        let steel = Material::Steel;
+        let link = Section::SteelTube {
+            width: Length::from_value_mm(100.),
+            wall_thickness: Length::from_value_mm(10.),
+        };
+        model.add_beam(
+            [center, tool_tip],
+            link.properties(),
+            steel.elastic_modulus() * 1000.,
+            steel.shear_modulus() * 1000.,
+            Axis::X,
+        );
+
+        model.add_load(tool_tip, direction, force);
+
+        let solution = model.solve();
+
+        FrameSolution {
+            tool_tip_deflection: solution.displacement(tool_tip, direction),
+            base_reactions: [
+                solution.reaction(bases[0], direction),
+                solution.reaction(bases[1], direction),
+            ],
+        }
+    }
+
+    /// Compliance against a force along the gantry beam
+    fn compliance_x(&self) -> FrameCompliance {
+        let h = self.column_height.value_m();
+        let l = self.gantry_span.value_m();
+        let d = self.tool_offset.value_m();
+        let k = self.joints.rotational_stiffness.value_nm_per_rad();
+
+        // Each column takes half of the force. They are clamped at the base,
+        // and the gantry beam keeps their tops from rotating.
+        let p = 0.5;
+        let columns = p * h.powi(3) / (12. * self.columns.ei(Axis::X));
+
+        // With a flexible joint on each end, each column rotates as a whole.
+        let joints = p * h.powi(2) / (2. * k);
+
+        // The force acts below the gantry beam, so the beam is also loaded by
+        // a moment at mid-span, which rotates the x-axis carriage.
+        let gantry = l / (12. * self.gantry.ei(Axis::Z)) * d.powi(2);
+
+        FrameCompliance {
+            columns,
+            gantry,
+            joints,
+        }
+    }
+
+    /// Compliance against a force across the gantry beam
+    fn compliance_y(&self) -> FrameCompliance {
+        let h = self.column_height.value_m();
+        let l = self.gantry_span.value_m();
+        let d = self.tool_offset.value_m();
+        let k = self.joints.rotational_stiffness.value_nm_per_rad();
+
+        // Each column is a cantilever, loaded by half the force. The force
+        // acts at the height of the tool tip (`a`), but is transmitted to the
+        // top of the column through the gantry beam.
+        let p = 0.5;
+        let a = h - d;
+        let columns = p
+            * (a * h.powi(2) / 2.
+                - h.powi(3) / 6.
+                - (a * h - h.powi(2) / 2.) * d)
+            / self.columns.ei(Axis::Y);
+
+        // The gantry beam bends across its span, and is twisted by the force
+        // acting below it. Let's treat its ends as simply supported for
+        // bending, which is the pessimistic assumption.
+        let bending = l.powi(3) / (48. * self.gantry.ei(Axis::Y));
+        let torsion = 0.5 * (l / 2.) / self.gantry.gj() * d.powi(2);
+
+        // The joints at the base rotate under the bending moment of the
+        // columns, the joints at the top under the torque of the beam.
+        let base_joints = p * a.powi(2) / k;
+        let top_joints = p * d.powi(2) / k;
+
+        FrameCompliance {
+            columns,
+            gantry: bending + torsion,
+            joints: base_joints + top_joints,
+        }
+    }
+}
+
+/// The compliance of the frame at the tool tip, in m/N
+///
+/// See [`FixedGantryFrame::compliance`].
+pub struct FrameCompliance {
+    pub columns: f64,
+    pub gantry: f64,
+    pub joints: f64,
+}
+
+impl FrameCompliance {
+    /// The compliance of the whole frame, in m/N
+    pub fn total(&self) -> f64 {
+        self.columns + self.gantry + self.joints
+    }
+}
+
+/// A member of the frame
+pub struct Member {
+    pub section: Section,
+
+    /// The direction of bending for which the strong axis of the section
+    /// applies
+    pub strong_direction: Axis,
+}
+
+impl Member {
+    fn add_to(&self, model: &mut Model, nodes: [fem::NodeId; 2]) {
+        let material = self.section.material();
+        model.add_beam(
+            nodes,
+            self.section.properties(),
+            material.elastic_modulus(),
+            material.shear_modulus(),
+            self.strong_direction,
+        );
+    }
+
+    /// The mass of a member of the given length
+    pub fn mass(&self, length: Length) -> Mass {
+        let volume = Volume::from_value_m3(
+            self.section.properties().area.value_m2() * length.value_m(),
+        );
+        self.section.material().mass(volume)
+    }
+
+    /// The bending stiffness (E·I) for bending in the given direction, in Nm²
+    pub fn ei(&self, direction: Axis) -> f64 {
+        let properties = self.section.properties();
+        let i = if direction == self.strong_direction {
+            properties.i_strong
+        } else {
+            properties.i_weak
+        };
+
+        self.section.material().elastic_modulus().value_pa() * i.value_m4()
+    }
+
+    /// The torsional stiffness (G·J) in Nm²
+    pub fn gj(&self) -> f64 {
+        self.section.material().shear_modulus().value_pa()
+            * self.section.properties().torsion_constant.value_m4()
+    }
+}
+
+/// A joint between members of the frame
+pub struct Joint {
+    pub name: &'static str,
+    pub rotational_stiffness: RotationalStiffness,
+}
+
+/// The result of [`FixedGantryFrame::solve`]
+pub struct FrameSolution {
+    /// The deflection of the tool tip in the direction of the force
+    pub tool_tip_deflection: Length,
+
+    /// The reaction forces at the base of both columns
+    pub base_reactions: [Force; 2],
+}
@@ -0,0 +1,242 @@
+use std::fmt;
+
+use crate::physics::{Area, Length, SecondMomentOfArea};
+
+use super::material::Material;
+
+/// The cross-section of a frame member
+#[derive(Clone, Copy, Debug)]
+pub enum Section {
+    /// A rectangular aluminium plate
+    Plate { width: Length, thickness: Length },
+
+    /// An aluminium extrusion profile
+    Extrusion(Profile),
+
+    /// A square steel tube
+    SteelTube {
+        width: Length,
+        wall_thickness: Length,
+    },
+}
+
+impl Section {
+    pub fn material(&self) -> Material {
+        match self {
+            Section::Plate { .. } | Section::Extrusion(_) => {
+                Material::Aluminium
+            }
+            Section::SteelTube { .. } => Material::Steel,
+        }
+    }
+
+    /// The outer dimensions, in the strong and the weak direction
+    pub fn dimensions(&self) -> [Length; 2] {
+        match *self {
+            Section::Plate { width, thickness } => [width, thickness],
+            Section::Extrusion(profile) => profile.dimensions(),
+            Section::SteelTube { width, .. } => [width, width],
+        }
+    }
+
+    pub fn properties(&self) -> SectionProperties {
+        match *self {
+            Section::Plate { width, thickness } => {
+                let b = width.value_m();
+                let t = thickness.value_m();
+
+                // Torsion constant of a thin rectangle, per Roark's formulas.
+                let torsion_constant = b * t.powi(3) / 3. * (1. - 0.63 * t / b);
+
+                let area = Area::from_value_m2(b * t);
+
+                SectionProperties {
+                    area,
+                    shear_area: area * (5. / 6.),
+                    i_strong: SecondMomentOfArea::from_value_m4(
+                        t * b.powi(3) / 12.,
+                    ),
+                    i_weak: SecondMomentOfArea::from_value_m4(
+                        b * t.powi(3) / 12.,
+                    ),
+                    torsion_constant: SecondMomentOfArea::from_value_m4(
+                        torsion_constant,
+                    ),
+                }
+            }
+            Section::Extrusion(profile) => profile.properties(),
+            Section::SteelTube {
+                width,
+                wall_thickness,
+            } => {
+                let b = width.value_m();
+                let t = wall_thickness.value_m();
+                let b_inner = b - 2. * t;
+
+                let i = (b.powi(4) - b_inner.powi(4)) / 12.;
+
+                // Bredt's formula for thin-walled closed sections, using the
+                // centerline of the wall.
+                let b_mid = b - t;
+                let torsion_constant = t * b_mid.powi(3);
+
+                // Only the walls parallel to the load carry shear.
+                let area = Area::from_value_m2(b.powi(2) - b_inner.powi(2));
+
+                SectionProperties {
+                    area,
+                    shear_area: area * 0.5,
+                    i_strong: SecondMomentOfArea::from_value_m4(i),
+                    i_weak: SecondMomentOfArea::from_value_m4(i),
+                    torsion_constant: SecondMomentOfArea::from_value_m4(
+                        torsion_constant,
+                    ),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Section::Plate { width, thickness } => write!(
+                f,
+                "aluminium plate {}x{} mm",
+                width.value_mm(),
+                thickness.value_mm()
+            ),
+            Section::Extrusion(profile) => {
+                write!(f, "aluminium extrusion {profile}")
+            }
+            Section::SteelTube {
+                width,
+                wall_thickness,
+            } => write!(
+                f,
+                "steel tube {}x{}x{} mm",
+                width.value_mm(),
+                width.value_mm(),
+                wall_thickness.value_mm()
+            ),
+        }
+    }
+}
+
+/// A standard aluminium extrusion profile
+#[derive(Clone, Copy, Debug)]
+pub enum Profile {
+    P2020,
+    P2040,
+    P3030,
+    P3060,
+    P4040,
+    P4080,
+}
+
+impl Profile {
+    pub fn profiles() -> Vec<Self> {
+        vec![
+            Profile::P2020,
+            Profile::P2040,
+            Profile::P3030,
+            Profile::P3060,
+            Profile::P4040,
+            Profile::P4080,
+        ]
+    }
+
+    /// The identifier of a frame made from this profile
+    ///
+    /// See [`FixedGantryFrame::id`](super::FixedGantryFrame::id).
+    pub fn id(&self) -> &'static str {
+        match self {
+            Profile::P2020 => "aluminium-extrusion-2020",
+            Profile::P2040 => "aluminium-extrusion-2040",
+            Profile::P3030 => "aluminium-extrusion-3030",
+            Profile::P3060 => "aluminium-extrusion-3060",
+            Profile::P4040 => "aluminium-extrusion-4040",
+            Profile::P4080 => "aluminium-extrusion-4080",
+        }
+    }
+
+    /// The outer dimensions, in the strong and the weak direction
+    pub fn dimensions(&self) -> [Length; 2] {
+        let (strong_mm, weak_mm) = match self {
+            Profile::P2020 => (20., 20.),
+            Profile::P2040 => (40., 20.),
+            Profile::P3030 => (30., 30.),
+            Profile::P3060 => (60., 30.),
+            Profile::P4040 => (40., 40.),
+            Profile::P4080 => (80., 40.),
+        };
+
+        [
+            Length::from_value_mm(strong_mm),
+            Length::from_value_mm(weak_mm),
+        ]
+    }
+
+    pub fn properties(&self) -> SectionProperties {
+        // Typical values for the "light" profiles from the Motedis and MISUMI
+        // catalogues. They vary a bit between manufacturers and slot types,
+        // but not by much. Torsion constants are rarely listed. Those are
+        // rough estimates, and the open slots make them pretty low.
+        let (area_cm2, i_strong_cm4, i_weak_cm4, torsion_constant_cm4) =
+            match self {
+                Profile::P2020 => (1.6, 0.7, 0.7, 0.15),
+                Profile::P2040 => (2.8, 4.6, 1.3, 0.4),
+                Profile::P3030 => (3.6, 2.8, 2.8, 0.6),
+                Profile::P3060 => (6.6, 19.5, 5.3, 1.7),
+                Profile::P4040 => (5.8, 9.0, 9.0, 2.0),
+                Profile::P4080 => (11.0, 45.0, 15.0, 6.0),
+            };
+
+        // The material is concentrated in the outer walls and the core, so
+        // let's assume only about half of it carries shear, as for a tube.
+        let area = Area::from_value_cm2(area_cm2);
+
+        SectionProperties {
+            area,
+            shear_area: area * 0.5,
+            i_strong: SecondMomentOfArea::from_value_cm4(i_strong_cm4),
+            i_weak: SecondMomentOfArea::from_value_cm4(i_weak_cm4),
+            torsion_constant: SecondMomentOfArea::from_value_cm4(
+                torsion_constant_cm4,
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Profile::P2020 => "20x20",
+            Profile::P2040 => "20x40",
+            Profile::P3030 => "30x30",
+            Profile::P3060 => "30x60",
+            Profile::P4040 => "40x40",
+            Profile::P4080 => "40x80",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// The properties of a cross-section that are relevant for its stiffness
+#[derive(Clone, Copy, Debug)]
+pub struct SectionProperties {
+    pub area: Area,
+
+    /// The area that is effective against shear deformation
+    pub shear_area: Area,
+
+    /// The second moment of area, when bending around the strong axis
+    pub i_strong: SecondMomentOfArea,
+
+    /// The second moment of area, when bending around the weak axis
+    pub i_weak: SecondMomentOfArea,
+
+    /// The torsion constant
+    pub torsion_constant: SecondMomentOfArea,
+}
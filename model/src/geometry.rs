@@ -0,0 +1,460 @@
+//! Geometry of the machine
+//!
+//! This lays out the machine's parts from the same values that the
+//! calculations use, so the model shows what is actually being sized. All
+//! parts are simple blocks and cylinders, which is all we need at this stage
+//! (and about all that Fornjot can do right now).
+//!
+//! The origin is at the bottom center of the machine. The x-axis runs along
+//! the gantry, the y-axis along the travel of the table or gantry (the front of
+//! the machine is towards negative y), and the z-axis points up. The y-axis is
+//! shown at the center of its travel, and the spindle with the z-axis at its
+//! highest position. All values are in mm.
+
+use fj::syntax::*;
+
+use crate::{
+    frame::{FixedGantryFrame, Member},
+    machine::{
+        axes::{self, Axis},
+        configuration::Configuration,
+        layout::Layout,
+        spindle::SpindleGeometry,
+    },
+};
+
+/// A part of the machine
+pub struct Part {
+    /// The assembly the part belongs to
+    pub group: &'static str,
+    pub name: &'static str,
+    pub primitive: Primitive,
+    pub color: [u8; 4],
+}
+
+/// The shape of a [`Part`]
+pub enum Primitive {
+    /// A block, optionally with a vertical bore through its center
+    Block {
+        center: [f64; 3],
+        size: [f64; 3],
+        bore: Option<f64>,
+    },
+
+    /// A vertical cylinder, standing on `base`
+    Cylinder {
+        base: [f64; 3],
+        diameter: f64,
+        height: f64,
+    },
+}
+
+impl Primitive {
+    /// The axis-aligned bounding box, as minimum and maximum corner
+    pub fn bounding_box(&self) -> [[f64; 3]; 2] {
+        match *self {
+            Primitive::Block { center, size, .. } => [
+                [0, 1, 2].map(|i| center[i] - size[i] / 2.),
+                [0, 1, 2].map(|i| center[i] + size[i] / 2.),
+            ],
+            Primitive::Cylinder {
+                base,
+                diameter,
+                height,
+            } => {
+                let r = diameter / 2.;
+                [
+                    [base[0] - r, base[1] - r, base[2]],
+                    [base[0] + r, base[1] + r, base[2] + height],
+                ]
+            }
+        }
+    }
+}
+
+/// Lay out all parts of the machine
+pub fn machine(
+    layout: &Layout,
+    frame: &FixedGantryFrame,
+    spindle: &SpindleGeometry,
+) -> Vec<Part> {
+    machine_at(layout, frame, spindle, 0.)
+}
+
+/// The area that the machine covers, along x and y
+///
+/// That includes what moves along y, at both ends of its travel. Whatever sticks
+/// out of the base there, be it the table or the spindle in front of the
+/// gantry, needs room too.
+pub fn footprint(
+    layout: &Layout,
+    frame: &FixedGantryFrame,
+    spindle: &SpindleGeometry,
+) -> [f64; 2] {
+    let travel = layout.envelope(Axis::Y).travel().value_mm();
+
+    let mut min = [f64::INFINITY; 2];
+    let mut max = [f64::NEG_INFINITY; 2];
+    for y in [-travel / 2., travel / 2.] {
+        for part in machine_at(layout, frame, spindle, y) {
+            let [part_min, part_max] = part.primitive.bounding_box();
+            for i in 0..2 {
+                min[i] = min[i].min(part_min[i]);
+                max[i] = max[i].max(part_max[i]);
+            }
+        }
+    }
+
+    [max[0] - min[0], max[1] - min[1]]
+}
+
+/// Lay out all parts of the machine, with the y-axis at the given position
+fn machine_at(
+    layout: &Layout,
+    frame: &FixedGantryFrame,
+    spindle: &SpindleGeometry,
+    y: f64,
+) -> Vec<Part> {
+    let aluminium = [190, 190, 200, 255];
+    let steel = [110, 110, 120, 255];
+    let carriage = [60, 60, 70, 255];
+
+    let mut parts = Vec::new();
+    let mut block = |group, name, center, size, color| {
+        parts.push(Part {
+            group,
+            name,
+            primitive: Primitive::Block {
+                center,
+                size,
+                bore: None,
+            },
+            color,
+        })
+    };
+
+    let [outer_x, outer_y, outer_z] = layout.outer.map(|l| l.value_mm());
+    let t = layout.plate_thickness.value_mm();
+    let clamp_width = layout.spindle_clamp_width.value_mm();
+
+    let rail = &layout.rail;
+    let rail_width = rail.rail_width.value_mm();
+    let rail_height = rail.rail_height.value_mm();
+    let height_total = rail.height_total.value_mm();
+    let carriage_width = rail.carriage_width.value_mm();
+    let carriage_length = rail.carriage_length.value_mm();
+    let carriage_height = height_total - rail_height;
+
+    block(
+        "frame",
+        "base plate",
+        [0., 0., t / 2.],
+        [outer_x, outer_y, t],
+        aluminium,
+    );
+
+    // Depending on the configuration, the y-axis moves either the table, or
+    // the gantry with everything on it.
+    let (table_position, gantry_position) = match layout.configuration {
+        Configuration::FixedGantry => (y, 0.),
+        Configuration::MovingGantry => (0., y),
+    };
+
+    let table = layout.table.map(|l| l.value_mm());
+    let table_thickness = axes::y::table_thickness().value_mm();
+    let column_height = frame.column_height.value_mm();
+    let [column_x, column_y, _] = size(&frame.columns, Axis::Z, column_height);
+    let y_rail_length = layout.rail_length(Axis::Y).value_mm();
+
+    match layout.configuration {
+        // The y-axis rails run along the base plate, between the screw
+        // supports. The table sits on top of their carriages.
+        Configuration::FixedGantry => {
+            let y_rail_offset = axes::y::rail_spacing().value_mm() / 2.;
+            for x in [-y_rail_offset, y_rail_offset] {
+                block(
+                    "rails",
+                    "y-axis rail",
+                    [x, 0., t + rail_height / 2.],
+                    [rail_width, y_rail_length, rail_height],
+                    steel,
+                );
+
+                let offset = (table[1] - carriage_length) / 2.;
+                for y in [table_position - offset, table_position + offset] {
+                    block(
+                        "rails",
+                        "y-axis carriage",
+                        [x, y, t + rail_height + carriage_height / 2.],
+                        [carriage_width, carriage_length, carriage_height],
+                        carriage,
+                    );
+                }
+            }
+            block(
+                "table",
+                "table",
+                [0., table_position, t + height_total + table_thickness / 2.],
+                [table[0], table[1], table_thickness],
+                aluminium,
+            );
+        }
+        // The table is bolted straight to the base plate. The y-axis rails
+        // run along its outer edges, under the columns, which stand on their
+        // carriages.
+        Configuration::MovingGantry => {
+            block(
+                "table",
+                "table",
+                [0., 0., t + table_thickness / 2.],
+                [table[0], table[1], table_thickness],
+                aluminium,
+            );
+
+            // The carriages are wider than the columns, so they're inset a
+            // bit, to keep them on the base plate.
+            let rail_offset = (outer_x - carriage_width.max(column_x)) / 2.;
+            for x in [-rail_offset, rail_offset] {
+                block(
+                    "rails",
+                    "y-axis rail",
+                    [x, 0., t + rail_height / 2.],
+                    [rail_width, y_rail_length, rail_height],
+                    steel,
+                );
+
+                let offset =
+                    (axes::y::gantry_depth().value_mm() - carriage_length) / 2.;
+                for y in [gantry_position - offset, gantry_position + offset] {
+                    block(
+                        "rails",
+                        "y-axis carriage",
+                        [x, y, t + rail_height + carriage_height / 2.],
+                        [carriage_width, carriage_length, carriage_height],
+                        carriage,
+                    );
+                }
+            }
+        }
+    }
+
+    // The columns stand at the outer edges, on the base plate, or on the
+    // y-axis carriages. Either way, their tops are at the same height, and
+    // the gantry beam spans between them, flush with those.
+    let column_bottom = match layout.configuration {
+        Configuration::FixedGantry => t,
+        Configuration::MovingGantry => t + height_total,
+    };
+    let column_top = t + column_height;
+    for x in [-1., 1.] {
+        block(
+            "frame",
+            "column",
+            [
+                x * (outer_x - column_x) / 2.,
+                gantry_position,
+                (column_bottom + column_top) / 2.,
+            ],
+            [column_x, column_y, column_top - column_bottom],
+            aluminium,
+        );
+    }
+    let gantry_length = outer_x - 2. * column_x;
+    let [_, gantry_y, gantry_z] = size(&frame.gantry, Axis::X, gantry_length);
+    let gantry_center = t + column_height - gantry_z / 2.;
+    block(
+        "gantry",
+        "gantry beam",
+        [0., gantry_position, gantry_center],
+        [gantry_length, gantry_y, gantry_z],
+        aluminium,
+    );
+
+    // The x-axis rails are on the front of the gantry beam. The x-axis
+    // carriage plate is mounted on their carriages.
+    let x_rails_front = gantry_position - gantry_y / 2.;
+    let x_rail_offset = axes::x::rail_spacing().value_mm() / 2.;
+    for z in [gantry_center - x_rail_offset, gantry_center + x_rail_offset] {
+        block(
+            "rails",
+            "x-axis rail",
+            [0., x_rails_front - rail_height / 2., z],
+            [
+                layout.rail_length(Axis::X).value_mm(),
+                rail_height,
+                rail_width,
+            ],
+            steel,
+        );
+
+        let offset = (clamp_width - carriage_length) / 2.;
+        for x in [-offset, offset] {
+            block(
+                "rails",
+                "x-axis carriage",
+                [x, x_rails_front - rail_height - carriage_height / 2., z],
+                [carriage_length, carriage_height, carriage_width],
+                carriage,
+            );
+        }
+    }
+    let x_plate_back = x_rails_front - height_total;
+    let x_plate_height = 2. * x_rail_offset + carriage_width;
+    block(
+        "gantry",
+        "x-axis carriage plate",
+        [0., x_plate_back - t / 2., gantry_center],
+        [clamp_width, t, x_plate_height],
+        aluminium,
+    );
+
+    // The z-axis rails are on the x-axis carriage plate.
+    let z_rails_front = x_plate_back - t;
+    let z_rail_offset = axes::z::rail_spacing().value_mm() / 2.;
+    let carriage_spacing = axes::z::carriage_spacing().value_mm();
+    let z_rail_length = layout.rail_length(Axis::Z).value_mm();
+    let z_carriages_center = gantry_center + z_rail_length / 2.
+        - carriage_length / 2.
+        - carriage_spacing / 2.;
+    for x in [-z_rail_offset, z_rail_offset] {
+        block(
+            "rails",
+            "z-axis rail",
+            [x, z_rails_front - rail_height / 2., gantry_center],
+            [rail_width, rail_height, z_rail_length],
+            steel,
+        );
+
+        for z in [
+            z_carriages_center - carriage_spacing / 2.,
+            z_carriages_center + carriage_spacing / 2.,
+        ] {
+            block(
+                "rails",
+                "z-axis carriage",
+                [x, z_rails_front - rail_height - carriage_height / 2., z],
+                [carriage_width, carriage_height, carriage_length],
+                carriage,
+            );
+        }
+    }
+
+    // The z-axis plate runs from the top of the carriages down to the bottom
+    // of the spindle clamp.
+    let nose = outer_z - layout.spindle_height.value_mm();
+    let clamp_center = nose + spindle.clamp_center().value_mm();
+    let clamp_height = 60.;
+    let spindle_diameter = spindle
+        .segments
+        .iter()
+        .map(|segment| segment.diameter.to_length().value_mm())
+        .fold(0., f64::max);
+    let clamp_depth = spindle_diameter + 30.;
+
+    let z_plate_back = z_rails_front - height_total;
+    let z_plate_top =
+        z_carriages_center + carriage_spacing / 2. + carriage_length / 2.;
+    let z_plate_bottom = clamp_center - clamp_height / 2.;
+    block(
+        "spindle",
+        "z-axis plate",
+        [
+            0.,
+            z_plate_back - t / 2.,
+            (z_plate_top + z_plate_bottom) / 2.,
+        ],
+        [clamp_width, t, z_plate_top - z_plate_bottom],
+        aluminium,
+    );
+
+    let spindle_axis = [0., z_plate_back - t - clamp_depth / 2.];
+    parts.push(Part {
+        group: "spindle",
+        name: "spindle clamp",
+        primitive: Primitive::Block {
+            center: [spindle_axis[0], spindle_axis[1], clamp_center],
+            size: [clamp_width, clamp_depth, clamp_height],
+            bore: Some(spindle_diameter),
+        },
+        color: aluminium,
+    });
+
+    let mut bottom = nose;
+    for segment in &spindle.segments {
+        let height = segment.height.value_mm();
+        parts.push(Part {
+            group: "spindle",
+            name: segment.name,
+            primitive: Primitive::Cylinder {
+                base: [spindle_axis[0], spindle_axis[1], bottom],
+                diameter: segment.diameter.to_length().value_mm(),
+                height,
+            },
+            color: segment.color,
+        });
+        bottom += height;
+    }
+
+    parts
+}
+
+/// Convert the parts into a Fornjot shape
+pub fn to_shape(parts: &[Part]) -> fj::Shape {
+    parts
+        .iter()
+        .map(|part| match part.primitive {
+            Primitive::Block { center, size, bore } => {
+                let [x, y, z] = size.map(|s| s / 2.);
+                let rectangle = fj::Sketch::from_points(vec![
+                    [-x, -y],
+                    [x, -y],
+                    [x, y],
+                    [-x, y],
+                ])
+                .with_color(part.color);
+                let sketch: fj::Shape2d = match bore {
+                    Some(diameter) => rectangle
+                        .difference(&circle(diameter, part.color))
+                        .into(),
+                    None => rectangle.into(),
+                };
+
+                sketch.sweep([0., 0., size[2]]).translate([
+                    center[0],
+                    center[1],
+                    center[2] - z,
+                ])
+            }
+            Primitive::Cylinder {
+                base,
+                diameter,
+                height,
+            } => circle(diameter, part.color)
+                .sweep([0., 0., height])
+                .translate(base),
+        })
+        .map(fj::Shape::from)
+        .reduce(|a, b| fj::Group { a, b }.into())
+        .unwrap()
+}
+
+fn circle(diameter: f64, color: [u8; 4]) -> fj::Sketch {
+    fj::Sketch::from_circle(fj::Circle::from_radius(diameter / 2.))
+        .with_color(color)
+}
+
+/// The size of a member along x, y, and z, given the axis it runs along
+fn size(member: &Member, along: Axis, length: f64) -> [f64; 3] {
+    let [strong, weak] = member.section.dimensions().map(|l| l.value_mm());
+
+    let mut size = [weak; 3];
+    let index = |axis| match axis {
+        Axis::X => 0,
+        Axis::Y => 1,
+        Axis::Z => 2,
+    };
+    size[index(along)] = length;
+    size[index(member.strong_direction)] = strong;
+
+    size
+}
@@ -23,44 +23,944 @@
 //!
 //! [Fornjot]: https://www.fornjot.app/
 
+mod analysis;
+mod bom;
+mod budget;
+mod compliance;
+mod config;
+mod cost;
+mod design;
+mod dynamics;
+mod frame;
+mod geometry;
 mod machine;
+mod openscad;
+mod optimizer;
 mod physics;
+mod stl;
+mod toolpath;
 mod tools;
 
-use std::fmt;
-
-use physics::Power;
+use std::{error::Error, fmt, fs, path::Path, time::Duration};
 
 use crate::{
-    machine::{axes, rails::mgn15_height_total, spindle::Spindle},
-    physics::{Force, Radius},
+    analysis::{Analysis, Diff},
+    budget::Budget,
+    config::MachineConfig,
+    design::Design,
+    frame::FixedGantryFrame,
+    machine::{
+        axes::{self, Axis},
+        configuration::{Configuration, Kinematics},
+        drives::{self, AxisDrive},
+        layout::Layout,
+        limits,
+        motors::StepperMotor,
+        power_supply::PowerSupply,
+        resolution::{Resolution, RiskLevel},
+        spindle::Spindle,
+    },
+    optimizer::DesignSpace,
+    physics::{Force, Length, Radius, RotationalSpeed, Speed, Volume},
+    stl::Mesh,
+    toolpath::{
+        loads::CuttingLoads,
+        planner::{self, Planner, Profile},
+        stock::Stock,
+        Motion, Toolpath,
+    },
     tools::Tool,
 };
 
+/// The machine is described by a configuration file in `machines/`
+///
+/// `machine` is the name of a file in there, without the extension, or a path
+/// to any other configuration file. If `compare` names another one, only the
+/// differences between the two designs are printed.
+///
+/// `program` is a G-code program in `gcode/`, or a path to any other one, to
+/// check against the machine, and to time on both designs when comparing.
+/// Leave it empty to skip that.
 #[fj::model]
-fn cnc() -> fj::Shape {
-    let spindle = Spindle::new(Power::from_value_kw(1.5));
-    let tools = Tool::tools();
+fn cnc(
+    #[param(default = String::from("fixed-gantry"))] machine: String,
+    #[param(default = String::new())] compare: String,
+    #[param(default = String::from("example"))] program: String,
+) -> Result<fj::Shape, Box<dyn Error + Send + Sync>> {
+    let config_path = resolve(machine);
+    let config = MachineConfig::load(&config_path)?;
 
-    let (worst_case_force, tool) = tools
-        .into_iter()
-        .map(|tool| {
+    if !compare.is_empty() {
+        let other_path = resolve(compare);
+        let other = MachineConfig::load(&other_path)?;
+        println!("Comparing {config_path} to {other_path}");
+
+        // Same program on both, to compare how long it takes.
+        let toolpath = if program.is_empty() {
+            None
+        } else {
+            Some(Toolpath::load(resolve_program(program))?)
+        };
+
+        let design = Design::new(&config)?;
+        let other_design = Design::new(&other)?;
+        print!(
+            "{}",
+            Diff::new(
+                Analysis::new(&config, &design, toolpath.as_ref()),
+                Analysis::new(&other, &other_design, toolpath.as_ref()),
+            )
+        );
+
+        let parts = geometry::machine(
+            &design.layout,
+            &design.frame,
+            &design.spindle_geometry,
+        );
+        return Ok(geometry::to_shape(&parts));
+    }
+
+    println!(
+        "Machine: {} ({}, from {config_path})",
+        config.name, config.configuration,
+    );
+
+    let design = Design::new(&config)?;
+    let Design {
+        spindle,
+        spindle_geometry,
+        spindle_clamp,
+        tools,
+        rail,
+        frame,
+        electronics,
+        layout,
+        drives,
+        ..
+    } = &design;
+
+    for tool in tools {
+        // Also figure out the torque that would require, and make sure it's
+        // below the torque that the spindle can deliver.
+        if design.overloads_spindle(tool) {
             let (tangential_cutting_force, tool_torque) =
                 tool.tangential_cutting_force();
-
-            // Also figure out the torque that would require, and make sure it's
-            // below the torque that the spindle can deliver.
             let spindle_torque = spindle.torque(tool.desired_rpm());
-            if tool_torque > spindle_torque {
+            println!(
+                "Required torque ({tool_torque}) is larger than spindle \
+                torque ({spindle_torque})!",
+            );
+            println!("Tool: {tool:#?}");
+            println!("Tangential cutting force: {tangential_cutting_force}");
+        }
+    }
+    let tool = &design.worst_case_tool;
+
+    println!(
+        "Worst-case tangential cutting force: {}",
+        design.worst_case_force,
+    );
+    println!("Tool: {tool:#?}");
+
+    // For sizing the axis drives, let's assume that the full worst-case
+    // cutting force can act along any of the axes, while it's accelerating.
+    let cutting_force = design.cutting_force();
+    let acceleration = drives::design_acceleration();
+
+    let y_axis_rail_max_distance_to_force =
+        Radius::from_length(axes::y::max_distance_to_force(rail));
+    let y_axis_rail_worst_case_torque =
+        cutting_force.to_torque(y_axis_rail_max_distance_to_force);
+    println!(
+        "Worst-case torque at y-axis rail: {}",
+        y_axis_rail_worst_case_torque
+    );
+
+    let supply_voltage = design.supply_voltage();
+
+    println!("Stepper motors (pull-out torque at {supply_voltage}):");
+    for motor in StepperMotor::motors() {
+        println!(
+            "- {} ({}): {} holding torque, {}, {}, {}, {} rotor inertia",
+            motor.name,
+            motor.frame,
+            motor.holding_torque,
+            motor.rated_current,
+            motor.phase_resistance,
+            motor.phase_inductance,
+            motor.rotor_inertia,
+        );
+        for speed_rpm in [150., 300., 600., 900., 1200., 1500.] {
+            let speed = RotationalSpeed::from_value_rpm(speed_rpm);
+            let torque = motor.pull_out_torque(speed, supply_voltage);
+            println!("    {speed}: {torque}");
+        }
+    }
+
+    for drive in drives {
+        println!(
+            "{}-axis drive: {} ({} microsteps), {} with {}, moving {}",
+            drive.axis,
+            drive.driver.name,
+            drive.microsteps,
+            drive.motor.name,
+            drive.screw.name,
+            drive.moving_mass,
+        );
+        println!(
+            "  Axial force at {acceleration}: {}",
+            drive.axial_force(cutting_force, acceleration),
+        );
+        println!(
+            "  Required motor torque: {}",
+            drive.required_torque(cutting_force, acceleration),
+        );
+
+        let inertia_ratio = drive.inertia_ratio();
+        println!(
+            "  Inertia ratio (load/rotor): {inertia_ratio:.2} ({})",
+            if inertia_ratio <= AxisDrive::MAX_INERTIA_RATIO {
+                "ok"
+            } else {
+                "too high"
+            }
+        );
+
+        for feed_mm_per_min in [500., 1000., 2000., 3000., 4000., 5000.] {
+            let speed = Speed::from_value_mm_per_min(feed_mm_per_min);
+            let margin = drive.torque_margin(
+                speed,
+                cutting_force,
+                acceleration,
+                supply_voltage,
+            );
+            println!(
+                "    {speed}: {} available, margin {margin:.2} ({})",
+                drive.available_torque(speed, supply_voltage),
+                if margin >= drive.safety_factor {
+                    "ok"
+                } else {
+                    "insufficient"
+                }
+            );
+        }
+    }
+
+    let limits = design.limits();
+    for l in &limits {
+        println!("{}-axis limits:", l.axis);
+        println!("  Max feed velocity: {}", l.max_feed_velocity);
+        println!("  Max rapid velocity: {}", l.max_rapid_velocity);
+        println!("  Max acceleration: {}", l.max_acceleration);
+    }
+    print!("grbl settings:\n{}", limits::grbl_settings(&limits));
+
+    // Open-loop steppers don't notice when they lose steps, so let's check how
+    // fine the axes can position, and how close they come to stalling.
+    for drive in drives {
+        let resolution = Resolution::new(drive);
+        println!("{}-axis resolution:", drive.axis);
+        println!(
+            "  Full step: {:.4} mm, microstep: {:.4} mm",
+            resolution.full_step.value_mm(),
+            resolution.microstep.value_mm(),
+        );
+        println!(
+            "  Effective: {:.4} mm ({} of {} microsteps resolved)",
+            resolution.effective.value_mm(),
+            resolution.effective_microsteps,
+            drive.microsteps,
+        );
+
+        let risks = design.stall_risks(drive);
+        let worst = risks
+            .iter()
+            .reduce(|a, b| if a.margin < b.margin { a } else { b })
+            .unwrap();
+        println!(
+            "  Smallest margin to stall: {:.2} ({}, at {})",
+            worst.margin, worst.tool, worst.feed,
+        );
+        for risk in &risks {
+            let level = risk.level();
+            if level != RiskLevel::Safe {
+                println!(
+                    "  {level}: {} at {} with {} (margin {:.2})",
+                    risk.tool, risk.feed, risk.cutting_force, risk.margin,
+                );
+            }
+        }
+    }
+
+    let clamp_center = spindle_geometry.clamp_center();
+    println!(
+        "Spindle: {} mm high, {}, center of gravity {:.1} mm above nose, \
+        clamp {} mm above nose",
+        spindle_geometry.height_total().value_mm(),
+        spindle_geometry.mass,
+        spindle_geometry.center_of_gravity().value_mm(),
+        clamp_center.value_mm(),
+    );
+    let lever_arm = Radius::from_length(tool.stick_out() + clamp_center);
+    println!(
+        "  Worst-case torque at spindle clamp: {}",
+        cutting_force.to_torque(lever_arm),
+    );
+
+    // The vendor's STL files have more detail than the segments above.
+    let cad = concat!(env!("CARGO_MANIFEST_DIR"), "/../cad");
+    let spindle_mesh = Mesh::load(format!("{cad}/Spindle.stl"))?;
+    let spindle_volume = spindle_mesh.mass_properties(1.).volume;
+    let spindle_density =
+        spindle_geometry.mass.value_kg() / spindle_volume.value_m3();
+    let spindle_properties = spindle_mesh.mass_properties(spindle_density);
+    print_mesh("Spindle.stl", &spindle_mesh);
+    println!(
+        "  {}, {} at {spindle_density:.0} kg/m³ (effective density)",
+        spindle_properties.volume, spindle_properties.mass,
+    );
+    print_mass_properties(&spindle_properties);
+
+    print_mesh("Spindle Holder.stl", &spindle_clamp.mesh);
+    println!(
+        "  {}, {} in aluminium",
+        spindle_clamp.properties.volume, spindle_clamp.properties.mass,
+    );
+    print_mass_properties(&spindle_clamp.properties);
+
+    // The spindle holder sets the width of the clamp, which costs x-axis
+    // travel, and its mass, which the x- and z-axes move.
+    println!(
+        "Layout ({} x {} x {} mm outer dimensions):",
+        layout.outer[0].value_mm(),
+        layout.outer[1].value_mm(),
+        layout.outer[2].value_mm(),
+    );
+    let mut travel = Vec::new();
+    for axis in [Axis::X, Axis::Y, Axis::Z] {
+        let envelope = layout.envelope(axis);
+        let consumers = envelope
+            .consumers
+            .iter()
+            .map(|(name, length)| format!("{name} {} mm", length.value_mm()))
+            .collect::<Vec<_>>();
+        println!(
+            "  {}: {} mm travel ({})",
+            envelope.axis,
+            envelope.travel().value_mm(),
+            consumers.join(", "),
+        );
+        travel.push(envelope.travel());
+    }
+    println!(
+        "  Work area: {} x {} mm (table: {} x {} mm)",
+        travel[0].value_mm(),
+        travel[1].value_mm(),
+        layout.table[0].value_mm(),
+        layout.table[1].value_mm(),
+    );
+    if travel[2].value_mm() < axes::z::max_travel().value_mm() {
+        println!(
+            "  z-axis travel is less than the assumed max travel of {} mm!",
+            axes::z::max_travel().value_mm(),
+        );
+    }
+
+    let stack_up = design.stack_up();
+    println!(
+        "Spindle nose above table: {} to {} mm (vise: {} mm)",
+        stack_up.nose_min.value_mm(),
+        stack_up.nose_max.value_mm(),
+        stack_up.vise_height.value_mm(),
+    );
+    let (reaching, not_reaching): (Vec<_>, Vec<_>) =
+        tools.iter().partition(|tool| stack_up.reaches_table(tool));
+    println!("  {} tools can reach the table", reaching.len());
+    for tool in not_reaching {
+        println!(
+            "  Can't reach the table: {tool} ({} mm stick-out)",
+            tool.stick_out().value_mm(),
+        );
+    }
+    for stock_height_mm in [10., 20., 30., 50., 75.] {
+        let stock_height = Length::from_value_mm(stock_height_mm);
+        let fitting = tools
+            .iter()
+            .filter(|tool| stack_up.fits_above(tool, stock_height))
+            .count();
+        println!(
+            "  {stock_height_mm} mm stock: {fitting} of {} tools fit above it",
+            tools.len(),
+        );
+    }
+    let max_stock_height = Length::from_value_mm(50.);
+    let [nose_low, nose_high] =
+        stack_up.required_nose_range(tools, max_stock_height);
+    println!(
+        "  Required for {} mm stock: nose from {} to {} mm, {} mm of z travel",
+        max_stock_height.value_mm(),
+        nose_low.value_mm(),
+        nose_high.value_mm(),
+        nose_high.value_mm() - nose_low.value_mm(),
+    );
+
+    for frame in FixedGantryFrame::options() {
+        println!(
+            "Frame ({}): columns {}, gantry {}, joints: {}",
+            frame.name,
+            frame.columns.section,
+            frame.gantry.section,
+            frame.joints.name,
+        );
+        for direction in [Axis::X, Axis::Y] {
+            let stiffness = frame.stiffness(direction);
+            println!(
+                "  {direction}: {stiffness}, tool-tip deflection {:.1} µm",
+                stiffness.to_deflection(cutting_force).value_mm() * 1000.,
+            );
+
+            let solution = frame.solve(direction, cutting_force);
+            println!(
+                "  {direction} (finite elements): tool-tip deflection {:.1} µm, \
+                base reactions {} / {}",
+                solution.tool_tip_deflection.value_mm() * 1000.,
+                solution.base_reactions[0],
+                solution.base_reactions[1],
+            );
+        }
+    }
+
+    for direction in [Axis::X, Axis::Y] {
+        let chain = design.compliance(direction, tool);
+        let stiffness = chain.total_stiffness();
+        println!(
+            "Compliance chain ({}, {} frame): {stiffness}, tool-tip \
+            deflection {:.1} µm",
+            chain.direction,
+            frame.name,
+            stiffness.to_deflection(cutting_force).value_mm() * 1000.,
+        );
+        for (element, share) in chain.ranked() {
+            println!(
+                "  {:>5.1}% {}: {} ({})",
+                share * 100.,
+                element.name,
+                element.stiffness,
+                element.source,
+            );
+        }
+    }
+
+    // The README narrows the configuration down to these. Same tools, same
+    // spindle, same parts, just arranged differently.
+    println!(
+        "Configurations ({cutting_force} at the tool tip, accelerating at \
+        {acceleration}):"
+    );
+    let motor_masses = [Axis::X, Axis::Y, Axis::Z].map(|axis| {
+        drives
+            .iter()
+            .find(|drive| drive.axis == axis)
+            .unwrap()
+            .motor
+            .mass
+    });
+    for configuration in Configuration::all() {
+        let layout = Layout {
+            configuration,
+            ..layout.clone()
+        };
+        let kinematics = Kinematics::new(&layout, frame, motor_masses);
+        let travel = [Axis::X, Axis::Y, Axis::Z]
+            .map(|axis| layout.envelope(axis).travel().value_mm());
+        let footprint = geometry::footprint(&layout, frame, spindle_geometry);
+
+        println!(
+            "- {}{}",
+            kinematics.configuration,
+            if configuration == config.configuration {
+                " (configured)"
+            } else {
+                ""
+            },
+        );
+        println!(
+            "    Footprint: {} x {} mm, work area {} x {} mm ({:.0}% of \
+            footprint), {} mm of z travel",
+            footprint[0],
+            footprint[1],
+            travel[0],
+            travel[1],
+            travel[0] * travel[1] / (footprint[0] * footprint[1]) * 100.,
+            travel[2],
+        );
+        for axis in &kinematics.axes {
+            println!(
+                "    {}: moves {} ({}), rail moment {}",
+                axis.axis,
+                axis.carries,
+                axis.moving_mass,
+                axis.rail_moment(cutting_force, acceleration),
+            );
+        }
+    }
+
+    // The machine is going to live in an apartment, and chatter is loud. The
+    // gantry is weaker across its span, so that's where chatter will start.
+    println!(
+        "Chatter (y, speeds between {} and {}):",
+        spindle.min_speed, spindle.max_speed,
+    );
+    for tool in tools {
+        let (dynamics, lobes) = design.chatter(tool);
+        let dominant = dynamics.dominant_mode();
+
+        let desired_speed = spindle.speed(tool.desired_rpm());
+        let (best_speed, best_depth) = lobes.best_speed();
+        println!("- {tool}");
+        println!(
+            "    Dominant mode: {} ({:.0} N/mm, {:.0}% damping)",
+            dominant.natural_frequency,
+            dominant.stiffness.value_n_per_m() / 1000.,
+            dominant.damping_ratio * 100.,
+        );
+        println!(
+            "    Stable depth: {:.2} mm at any speed, {:.2} mm at {desired_speed}, \
+            {:.2} mm at {best_speed}",
+            lobes.critical_depth.value_mm(),
+            lobes.limit_at(desired_speed).value_mm(),
+            best_depth.value_mm(),
+        );
+    }
+
+    println!("Power supply: {}", electronics.power_supply.name);
+    let required_power = electronics.required_power(drives);
+    match PowerSupply::recommend(supply_voltage, required_power) {
+        Some(power_supply) => println!(
+            "Required power: {required_power} (recommended: {})",
+            power_supply.name
+        ),
+        None => println!(
+            "Required power: {required_power} (no power supply in catalogue \
+            is sufficient at {supply_voltage})"
+        ),
+    }
+    for problem in electronics.check(drives) {
+        println!("Problem: {problem}");
+    }
+
+    let bom = design.bom();
+    println!(
+        "Bill of materials: {} line items, {} rails, {} carriages",
+        bom.items.len(),
+        bom.quantity_of("rail"),
+        bom.quantity_of("carriage"),
+    );
+    for item in &bom.items {
+        print!("  {}x {}", item.quantity, item.name);
+        if !item.spec.is_empty() {
+            print!(" ({})", item.spec);
+        }
+        println!(" at {} from {}", item.unit_price, item.unit_price.vendor);
+    }
+    let bom_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../bom");
+    fs::write(format!("{bom_path}.csv"), bom.to_csv())?;
+    fs::write(format!("{bom_path}.md"), bom.to_markdown())?;
+    println!("Exported bill of materials: {bom_path}.csv, {bom_path}.md");
+
+    let budget = Budget::new(&bom, design.budget);
+    println!(
+        "Budget: {:.2} € of {:.2} €, {:.2} € {}",
+        budget.total(),
+        budget.limit,
+        budget.headroom().abs(),
+        if budget.headroom() >= 0. {
+            "left"
+        } else {
+            "over budget!"
+        },
+    );
+    for order in &budget.orders {
+        println!(
+            "  {} ({}): {:.2} € parts, {:.2} € shipping, {:.2} € import \
+            charges",
+            order.vendor.name,
+            order.vendor.source,
+            order.parts,
+            order.shipping,
+            order.import_charges,
+        );
+    }
+    for (category, cost) in &budget.categories {
+        println!(
+            "  {category}: {cost:.2} € ({:.0}%)",
+            cost / budget.total() * 100.,
+        );
+    }
+    // The BOM only covers what the model knows about so far.
+    println!("  Not included yet: frame, controller, wiring, fasteners");
+
+    let design_space = DesignSpace {
+        layout,
+        drives,
+        frame,
+        electronics,
+        spindle,
+        spindle_geometry,
+        tool,
+        cutting_force,
+        budget: budget.limit,
+        constraints: config.constraints(),
+    };
+    let search = design_space.search();
+    println!(
+        "Design search: {} combinations, {} on the Pareto front",
+        search.evaluated,
+        search.pareto_front.len(),
+    );
+    for (violation, count) in &search.rejected {
+        println!("  {count} rejected: {violation}");
+    }
+    for candidate in &search.pareto_front {
+        println!(
+            "  {:.2} € ({}): {}, {}, {}, {}, {}",
+            candidate.cost,
+            if candidate.cost <= budget.limit {
+                "within budget"
+            } else {
+                "over budget"
+            },
+            candidate.rail,
+            candidate.screw,
+            candidate.motor,
+            candidate.driver,
+            candidate.power_supply,
+        );
+        println!(
+            "    Tool-tip stiffness {}, max feed {}",
+            candidate.stiffness, candidate.max_feed,
+        );
+    }
+
+    if !program.is_empty() {
+        let program_path = resolve_program(program);
+        let toolpath = Toolpath::load(&program_path)?;
+
+        let count = |motion: fn(&Motion) -> bool| {
+            toolpath
+                .segments
+                .iter()
+                .filter(|segment| motion(&segment.motion))
+                .count()
+        };
+        let length = |rapid: bool| {
+            toolpath
+                .segments
+                .iter()
+                .filter(|segment| (segment.motion == Motion::Rapid) == rapid)
+                .map(|segment| segment.length().value_mm())
+                .fold(0., |a, b| a + b)
+        };
+        let dwells = count(|motion| matches!(motion, Motion::Dwell(_)));
+        println!(
+            "Program {program_path}: {} moves ({} rapid, {} linear, {} arcs)",
+            toolpath.segments.len() - dwells,
+            count(|motion| *motion == Motion::Rapid),
+            count(|motion| *motion == Motion::Linear),
+            count(|motion| matches!(motion, Motion::Arc { .. })),
+        );
+        if dwells > 0 {
+            let dwell_time = toolpath
+                .segments
+                .iter()
+                .filter_map(|segment| match segment.motion {
+                    Motion::Dwell(duration) => Some(duration),
+                    _ => None,
+                })
+                .sum();
+            println!(
+                "  {dwells} dwells, {} in total",
+                format_duration(dwell_time),
+            );
+        }
+        println!(
+            "  {:.0} mm at feed rate, {:.0} mm rapid",
+            length(false),
+            length(true),
+        );
+        for number in toolpath.tools() {
+            let tool = match toolpath::tool(number, tools) {
+                Some(tool) => tool.to_string(),
+                None => "not in the tool catalogue".to_string(),
+            };
+            let segments = toolpath
+                .segments
+                .iter()
+                .filter(|segment| segment.tool == Some(number))
+                .collect::<Vec<_>>();
+            println!("  T{number} (from line {}): {tool}", segments[0].line);
+
+            let feed_moves = segments
+                .iter()
+                .filter(|segment| segment.is_feed())
+                .collect::<Vec<_>>();
+            if feed_moves.is_empty() {
+                println!("    Rapids only");
+                continue;
+            }
+            let feeds = feed_moves
+                .iter()
+                .map(|segment| segment.feed.value_mm_per_min());
+            let speeds = feed_moves
+                .iter()
+                .filter_map(|segment| segment.spindle_speed)
+                .map(|speed| speed.value_rpm())
+                .collect::<Vec<_>>();
+            print!(
+                "    Feed {} to {} mm/min",
+                feeds.clone().fold(f64::INFINITY, f64::min),
+                feeds.fold(0., f64::max),
+            );
+            if speeds.is_empty() {
+                println!(", spindle off");
+            } else {
                 println!(
-                    "Required torque ({tool_torque}) is larger than spindle \
-                    torque ({spindle_torque})!",
+                    ", spindle {} to {} rpm",
+                    speeds.iter().copied().fold(f64::INFINITY, f64::min),
+                    speeds.iter().copied().fold(0., f64::max),
                 );
-                println!("Tool: {tool:#?}");
+            }
+        }
+
+        // The above is what the program says. Replaying it through the stock
+        // tells what it actually cuts, and what that asks of the machine.
+        let mut stock = Stock::new(
+            &toolpath,
+            tools,
+            spindle_geometry.collet_nut(),
+            Length::from_value_mm(0.25),
+        );
+        let cutting_loads = CuttingLoads {
+            tools,
+            spindle,
+            drives,
+            rail,
+            kinematics: &design.kinematics,
+            supply_voltage,
+            rail_safety_factor: design.constraints.rail_safety_factor,
+        };
+        let initial_volume = stock.volume();
+        let loads = cutting_loads.replay(&toolpath, &mut stock);
+        let [stock_width, stock_depth] = stock.size();
+        let cuts = loads
+            .iter()
+            .filter_map(|load| {
+                Some((load, load.cut.as_ref()?, load.forces.as_ref()?))
+            })
+            .collect::<Vec<_>>();
+        println!(
+            "  Cutting loads ({} x {} mm stock, top at z = 0): {} moves cut",
+            stock_width.value_mm(),
+            stock_depth.value_mm(),
+            cuts.len(),
+        );
+        if let Some((load, cut, forces)) =
+            cuts.iter().max_by(|(_, _, a), (_, _, b)| {
+                a.cutting_force
+                    .value_n()
+                    .total_cmp(&b.cutting_force.value_n())
+            })
+        {
+            println!(
+                "    Largest cutting force: {} at line {} ({:.2} mm deep, \
+                {:.2} mm wide, {:.4} mm per tooth)",
+                forces.cutting_force,
+                load.line,
+                cut.engagement.axial.value_mm(),
+                cut.engagement.radial.value_mm(),
+                forces.feed_per_tooth.value_mm(),
+            );
+            println!(
+                "    Spindle torque: {} of {} available",
+                forces.spindle_torque, forces.available_spindle_torque,
+            );
+        }
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let axis_loads = cuts.iter().flat_map(|(_, _, forces)| {
+                forces.axes.iter().filter(|load| load.axis == axis)
+            });
+            let rail_safety_factor = axis_loads
+                .clone()
+                .map(|load| load.rail_safety_factor)
+                .fold(f64::INFINITY, f64::min);
+            if let Some(load) = axis_loads
+                .min_by(|a, b| a.torque_margin.total_cmp(&b.torque_margin))
+            {
                 println!(
-                    "Tangential cutting force: {tangential_cutting_force}"
+                    "    {axis}: smallest motor torque margin {:.2} (at {}), \
+                    smallest rail safety factor {rail_safety_factor:.1}",
+                    load.torque_margin, load.speed,
                 );
+            }
+        }
+        let overloaded = loads
+            .iter()
+            .filter(|load| !load.overloads.is_empty())
+            .collect::<Vec<_>>();
+        if overloaded.is_empty() {
+            println!("    No line exceeds what the machine can take");
+        }
+        for load in overloaded {
+            let overloads = load
+                .overloads
+                .iter()
+                .map(|overload| overload.to_string())
+                .collect::<Vec<_>>();
+            println!("    Line {}: {}", load.line, overloads.join(", "));
+        }
+
+        // What's left of the stock, and what each move took off of it.
+        println!(
+            "  Material removal: {} of {} removed",
+            Volume::from_value_m3(
+                initial_volume.value_m3() - stock.volume().value_m3()
+            ),
+            initial_volume,
+        );
+        for load in &loads {
+            let Some(cut) = &load.cut else { continue };
+            if !cut.is_cutting() {
+                continue;
+            }
+            println!(
+                "    Line {}: {:.1} mm³, up to {:.2} mm deep and {:.2} mm wide",
+                load.line,
+                cut.removed.value_mm3(),
+                cut.max_engagement.axial.value_mm(),
+                cut.max_engagement.radial.value_mm(),
+            );
+        }
+        let stock_path = Path::new(&program_path).with_extension("stock");
+        let stock_path = stock_path.display();
+        fs::write(format!("{stock_path}.pgm"), stock.to_pgm())?;
+        stock.to_mesh().save(format!("{stock_path}.stl"))?;
+        println!("  Exported stock: {stock_path}.pgm, {stock_path}.stl");
+
+        // The feeds in the program are what the machine moves at, when the
+        // planner lets it.
+        let plan = Planner::new(&limits, Profile::Trapezoidal).plan(&toolpath);
+        let jerk = planner::default_jerk();
+        let jerk_limited =
+            Planner::new(&limits, Profile::JerkLimited(jerk)).plan(&toolpath);
+        println!(
+            "  Cycle time: {} (GRBL), {} (limited to {jerk}), {} without \
+            acceleration",
+            format_duration(plan.cycle_time()),
+            format_duration(jerk_limited.cycle_time()),
+            format_duration(plan.unaccelerated_time()),
+        );
+        let mut slowest = plan
+            .segments
+            .iter()
+            .filter_map(|segment| {
+                let programmed = segment.programmed?;
+                let ratio = segment.average().value_mm_per_min()
+                    / programmed.value_mm_per_min();
+                Some((segment, programmed, ratio))
+            })
+            .collect::<Vec<_>>();
+        slowest.sort_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+        println!("    Furthest below the programmed feed:");
+        for (segment, programmed, ratio) in slowest.iter().take(5) {
+            println!(
+                "    Line {}: {:.0} mm in {:.2} s, {} average, {} peak, \
+                {programmed} programmed ({:.0}%)",
+                segment.line,
+                segment.length.value_mm(),
+                segment.time.as_secs_f64(),
+                segment.average(),
+                segment.peak,
+                ratio * 100.,
+            );
+        }
+    }
+
+    let parts = geometry::machine(layout, frame, spindle_geometry);
+
+    // Export an OpenSCAD model next to the one in `cad/`, to compare them.
+    let scad_path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../cad/cnc-mill-generated.scad"
+    );
+    fs::write(scad_path, openscad::export(&parts))?;
+    println!("Exported OpenSCAD model: {scad_path}");
+
+    Ok(geometry::to_shape(&parts))
+}
+
+/// Resolve a machine name to the path of its configuration file
+fn resolve(machine: String) -> String {
+    if machine.ends_with(".toml") {
+        machine
+    } else {
+        format!("{}/../machines/{machine}.toml", env!("CARGO_MANIFEST_DIR"))
+    }
+}
+
+/// Resolve a program name to the path of its G-code file
+fn resolve_program(program: String) -> String {
+    if program.contains('.') {
+        program
+    } else {
+        format!("{}/../gcode/{program}.nc", env!("CARGO_MANIFEST_DIR"))
+    }
+}
+
+/// Format a duration as minutes and seconds
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs_f64();
+    format!("{}:{:04.1}", (seconds / 60.).floor(), seconds % 60.)
+}
+
+fn print_mesh(name: &str, mesh: &Mesh) {
+    let [min, max] = mesh.bounding_box();
+    let size = [0, 1, 2].map(|i| max[i].value_mm() - min[i].value_mm());
+    println!(
+        "{name}: {} triangles, {:.1} x {:.1} x {:.1} mm",
+        mesh.triangles.len(),
+        size[0],
+        size[1],
+        size[2],
+    );
+}
 
+fn print_mass_properties(properties: &stl::MassProperties) {
+    let [x, y, z] = properties.centroid.map(|c| c.value_mm());
+    println!("  Centroid: [{x:.1}, {y:.1}, {z:.1}] mm");
+    println!(
+        "  Inertia about centroid: {:.5} / {:.5} / {:.5} kg·m² (x/y/z)",
+        properties.inertia[0][0].value_kg_m2(),
+        properties.inertia[1][1].value_kg_m2(),
+        properties.inertia[2][2].value_kg_m2(),
+    );
+}
+
+/// Find the tool with the largest tangential cutting force
+///
+/// Tools that would require more torque than the spindle can deliver are
+/// limited by the spindle.
+fn worst_case_force(
+    tools: Vec<Tool>,
+    spindle: &Spindle,
+) -> (TangentialCuttingForce, Tool) {
+    tools
+        .into_iter()
+        .map(|tool| {
+            let (tangential_cutting_force, tool_torque) =
+                tool.tangential_cutting_force();
+
+            let spindle_torque = spindle.torque(tool.desired_rpm());
+            if tool_torque > spindle_torque {
                 return (
                     TangentialCuttingForce::PerMaxSpindleTorque(
                         spindle_torque.to_force(tool.diameter),
@@ -77,26 +977,7 @@ fn cnc() -> fj::Shape {
             )
         })
         .reduce(|a, b| if a.0 > b.0 { a } else { b })
-        .unwrap();
-
-    println!("Worst-case tangential cutting force: {}", worst_case_force);
-    println!("Tool: {tool:#?}");
-
-    let y_axis_rail_max_distance_to_force =
-        Radius::from_length(axes::y::table_thickness() + axes::z::max_travel())
-            + Radius::from_length(mgn15_height_total() / 2.);
-    let y_axis_rail_worst_case_torque = worst_case_force
-        .value()
-        .to_torque(y_axis_rail_max_distance_to_force);
-    println!(
-        "Worst-case torque at y-axis rail: {}",
-        y_axis_rail_worst_case_torque
-    );
-
-    // This is a placeholder. We don't actually need to export geometry right
-    // now, but Fornjot won't allow us to have a function that doesn't do that.
-    let w = 0.5;
-    fj::Sketch::from_points(vec![[-w, -w], [w, -w], [w, w], [-w, w]]).into()
+        .unwrap()
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -0,0 +1,20 @@
+use crate::physics::{Length, Mass};
+
+use super::z;
+
+/// The mass moved by the x-axis
+///
+/// That's everything the z-axis moves, plus the z-axis itself, including its
+/// motor.
+pub fn moving_mass(spindle_clamp: Mass, z_motor: Mass) -> Mass {
+    // This is a rough guess for the z-axis plate, rails, carriages, ball
+    // screw, and bearings.
+    let z_axis = Mass::from_value_kg(3.3);
+
+    z::moving_mass(spindle_clamp) + z_axis + z_motor
+}
+
+/// The vertical distance between the two rails on the gantry
+pub fn rail_spacing() -> Length {
+    Length::from_value_mm(100.)
+}
@@ -1,5 +1,47 @@
-use crate::physics::Length;
+use crate::{
+    machine::rails::Rail,
+    physics::{Length, Mass},
+};
+
+use super::z;
 
 pub fn table_thickness() -> Length {
     Length::from_value_mm(10.)
 }
+
+/// The distance between the two rails of the y-axis
+pub fn rail_spacing() -> Length {
+    // The table is 150 mm wide (see `cad/cnc-mill.scad`).
+    Length::from_value_mm(120.)
+}
+
+/// The depth of the gantry along y, in the moving-gantry configuration
+///
+/// That's the columns, with the z-axis and spindle in front of the gantry
+/// beam. It moves along with the tool, so it takes away from the travel.
+pub fn gantry_depth() -> Length {
+    // The same as the plates of the columns in the fixed-gantry design.
+    Length::from_value_mm(150.)
+}
+
+/// The maximum distance between the y-axis carriages and the cutting force
+///
+/// That's when the tool cuts at the top of the highest workpiece that fits
+/// under the spindle.
+pub fn max_distance_to_force(rail: &Rail) -> Length {
+    table_thickness() + z::max_travel() + rail.height_total / 2.
+}
+
+/// The mass moved by the y-axis
+///
+/// In the fixed-gantry configuration, that's the table and whatever is on it.
+pub fn moving_mass() -> Mass {
+    // 300x150 mm aluminium table (see `cad/cnc-mill.scad`), plus carriages.
+    let table = Mass::from_value_kg(1.5);
+
+    // A small vise and a reasonably large block of aluminium.
+    let vise = Mass::from_value_kg(3.);
+    let workpiece = Mass::from_value_kg(2.);
+
+    table + vise + workpiece
+}
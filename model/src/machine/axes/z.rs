@@ -1,4 +1,7 @@
-use crate::physics::Length;
+use crate::{
+    machine::spindle::SpindleGeometry,
+    physics::{Length, Mass},
+};
 
 /// The max travel of the z-axis
 ///
@@ -7,3 +10,33 @@ use crate::physics::Length;
 pub fn max_travel() -> Length {
     Length::from_value_mm(100.)
 }
+
+/// The mass moved by the z-axis
+///
+/// That's the spindle, the spindle clamp, and the plate they're mounted on.
+pub fn moving_mass(spindle_clamp: Mass) -> Mass {
+    let spindle = SpindleGeometry::new().mass;
+    let plate = Mass::from_value_kg(1.);
+
+    spindle + spindle_clamp + plate
+}
+
+/// The distance between the two z-axis rails
+pub fn rail_spacing() -> Length {
+    Length::from_value_mm(60.)
+}
+
+/// The distance between the two carriages on each z-axis rail
+pub fn carriage_spacing() -> Length {
+    Length::from_value_mm(80.)
+}
+
+/// The distance from the center of the z-axis carriages to the tool tip
+///
+/// This is for the z-axis at its lowest position, which is where the lever
+/// arm is largest.
+pub fn carriage_to_tool_tip() -> Length {
+    // The spindle clamp sits at the bottom of the z-axis plate, and the
+    // spindle nose and tool stick out below that.
+    Length::from_value_mm(150.)
+}
@@ -0,0 +1,196 @@
+//! The kinematic configuration of the machine
+//!
+//! The README narrows the choice down to fixed gantry and moving gantry. They
+//! use mostly the same parts, but distribute the masses differently between
+//! the axes, and load the rails differently. This models both, so they can be
+//! compared on the same tools and spindle.
+
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::{
+    frame::FixedGantryFrame,
+    physics::{Acceleration, Force, Length, Mass, Radius, Torque},
+};
+
+use super::{
+    axes::{x, y, z, Axis},
+    layout::Layout,
+};
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Configuration {
+    /// The tool moves along x and z, the table moves along y
+    FixedGantry,
+
+    /// The tool moves along all three axes, and the table is fixed
+    MovingGantry,
+}
+
+impl Configuration {
+    pub fn all() -> [Self; 2] {
+        [Configuration::FixedGantry, Configuration::MovingGantry]
+    }
+}
+
+impl fmt::Display for Configuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Configuration::FixedGantry => write!(f, "fixed gantry"),
+            Configuration::MovingGantry => write!(f, "moving gantry"),
+        }
+    }
+}
+
+/// Which axis moves what, and how that loads its rails
+pub struct Kinematics {
+    pub configuration: Configuration,
+
+    /// The axes, in the order x, y, z
+    pub axes: [AxisKinematics; 3],
+}
+
+impl Kinematics {
+    /// `motor_masses` are those of the x-, y-, and z-axis motors
+    ///
+    /// Some axes carry the motors of others.
+    pub fn new(
+        layout: &Layout,
+        frame: &FixedGantryFrame,
+        motor_masses: [Mass; 3],
+    ) -> Self {
+        let [x_motor, _, z_motor] = motor_masses;
+        let x_moving_mass = x::moving_mass(layout.spindle_clamp_mass, z_motor);
+
+        // The x- and z-axes are the same in both configurations. Only what
+        // the y-axis moves is different.
+        let x = AxisKinematics {
+            axis: Axis::X,
+            carries: "z-axis, spindle",
+            moving_mass: x_moving_mass,
+            lever_arm: frame.tool_offset,
+            // The z-axis and spindle hang below the gantry beam. Their center
+            // of mass is roughly halfway down to the tool tip.
+            mass_lever_arm: frame.tool_offset / 2.,
+            carriage_spacing: x::rail_spacing(),
+        };
+        let z = AxisKinematics {
+            axis: Axis::Z,
+            carries: "spindle, spindle clamp",
+            moving_mass: z::moving_mass(layout.spindle_clamp_mass),
+            lever_arm: z::carriage_to_tool_tip(),
+            mass_lever_arm: z::carriage_to_tool_tip() / 2.,
+            carriage_spacing: z::carriage_spacing(),
+        };
+
+        let y = match layout.configuration {
+            Configuration::FixedGantry => AxisKinematics {
+                axis: Axis::Y,
+                carries: "table, vise, workpiece",
+                moving_mass: y::moving_mass(),
+                lever_arm: y::max_distance_to_force(&layout.rail),
+                // The vise and workpiece are about half as high as the
+                // highest cut.
+                mass_lever_arm: y::max_distance_to_force(&layout.rail) / 2.,
+                carriage_spacing: y::rail_spacing(),
+            },
+            Configuration::MovingGantry => {
+                // A rough guess for the x-axis rails, ball screw, and
+                // bearings, which sit on the gantry beam, with its motor.
+                let x_axis = Mass::from_value_kg(2.3) + x_motor;
+
+                // Heights above the y-axis carriages, which the columns stand
+                // on.
+                let masses = [
+                    (
+                        frame.columns.mass(frame.column_height) * 2.,
+                        frame.column_height / 2.,
+                    ),
+                    (frame.gantry.mass(frame.gantry_span), frame.column_height),
+                    (x_axis, frame.column_height),
+                    (
+                        x_moving_mass,
+                        frame.column_height - frame.tool_offset / 2.,
+                    ),
+                ];
+                let moving_mass = masses
+                    .iter()
+                    .map(|(mass, _)| *mass)
+                    .reduce(|a, b| a + b)
+                    .unwrap();
+                let center_of_mass = masses
+                    .iter()
+                    .map(|(mass, height)| mass.value_kg() * height.value_m())
+                    .sum::<f64>()
+                    / moving_mass.value_kg();
+
+                AxisKinematics {
+                    axis: Axis::Y,
+                    carries: "gantry, x-axis, z-axis, spindle",
+                    moving_mass,
+                    // The table is fixed to the base now, but the rails are
+                    // still at about the same height, and so is the tool tip.
+                    lever_arm: y::max_distance_to_force(&layout.rail),
+                    mass_lever_arm: Length::from_value_m(center_of_mass),
+                    // The carriages under each column are spread over its
+                    // depth.
+                    carriage_spacing: y::gantry_depth(),
+                }
+            }
+        };
+
+        Self {
+            configuration: layout.configuration,
+            axes: [x, y, z],
+        }
+    }
+
+    pub fn axis(&self, axis: Axis) -> &AxisKinematics {
+        match axis {
+            Axis::X => &self.axes[0],
+            Axis::Y => &self.axes[1],
+            Axis::Z => &self.axes[2],
+        }
+    }
+}
+
+/// What an axis moves, and where its rails are loaded
+pub struct AxisKinematics {
+    pub axis: Axis,
+
+    /// The parts that the axis moves
+    pub carries: &'static str,
+
+    pub moving_mass: Mass,
+
+    /// The distance from the center of the carriages to the tool tip
+    ///
+    /// This is perpendicular to the rails, and the largest it gets over the
+    /// travel of the other axes.
+    pub lever_arm: Length,
+
+    /// The distance from the center of the carriages to the center of mass of
+    /// the moving mass
+    pub mass_lever_arm: Length,
+
+    /// The distance between the carriages that resist the tilting moment
+    pub carriage_spacing: Length,
+}
+
+impl AxisKinematics {
+    /// The tilting moment on the carriages
+    ///
+    /// That's from the cutting force at the tool tip, plus the force that
+    /// accelerates the moving mass.
+    pub fn rail_moment(
+        &self,
+        cutting_force: Force,
+        acceleration: Acceleration,
+    ) -> Torque {
+        cutting_force.to_torque(Radius::from_length(self.lever_arm))
+            + (self.moving_mass * acceleration)
+                .to_torque(Radius::from_length(self.mass_lever_arm))
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{
+    cost::Price,
+    physics::{Current, Frequency, Voltage},
+};
+
+/// A stepper motor driver
+#[derive(Debug)]
+pub struct StepperDriver {
+    pub name: &'static str,
+    pub max_current: Current,
+    pub min_voltage: Voltage,
+    pub max_voltage: Voltage,
+    pub max_microsteps: u32,
+    pub max_step_rate: Frequency,
+    pub price: Price,
+}
+
+impl StepperDriver {
+    pub fn drivers() -> Vec<Self> {
+        // The Makerbase drivers from the README. Max current is as listed by
+        // Makerbase, voltage range, microstepping, and max step rate are from
+        // the datasheets of the respective chips.
+        // Prices are from the Makerbase store on AliExpress.
+
+        macro_rules! drivers {
+            ($(
+                Self {
+                    name: $name:expr,
+                    max_current: $max_current:expr,
+                    voltage: $min_voltage:expr, $max_voltage:expr,
+                    max_microsteps: $max_microsteps:expr,
+                    max_step_rate_khz: $max_step_rate_khz:expr,
+                    price: $price:expr,
+                },
+            )*) => {
+                vec![
+                    $(
+                        Self {
+                            name: $name,
+                            max_current: Current::from_value_a($max_current),
+                            min_voltage: Voltage::from_value_v($min_voltage),
+                            max_voltage: Voltage::from_value_v($max_voltage),
+                            max_microsteps: $max_microsteps,
+                            max_step_rate: Frequency::from_value_khz(
+                                $max_step_rate_khz,
+                            ),
+                            price: $price,
+                        },
+                    )*
+                ]
+            };
+        }
+
+        drivers![
+            Self {
+                name: "MKS A4988",
+                max_current: 2.0,
+                voltage: 8., 35.,
+                max_microsteps: 16,
+                max_step_rate_khz: 500.,
+                price: Price::usd(2., "Makerbase"),
+            },
+            Self {
+                name: "MKS TMC2208",
+                max_current: 2.0,
+                voltage: 4.75, 36.,
+                max_microsteps: 256,
+                max_step_rate_khz: 6000.,
+                price: Price::usd(4., "Makerbase"),
+            },
+            Self {
+                name: "MKS TMC2209",
+                max_current: 2.5,
+                voltage: 4.75, 29.,
+                max_microsteps: 256,
+                max_step_rate_khz: 6000.,
+                price: Price::usd(5., "Makerbase"),
+            },
+            Self {
+                name: "MKS TMC2225",
+                max_current: 2.0,
+                voltage: 4.75, 36.,
+                max_microsteps: 256,
+                max_step_rate_khz: 6000.,
+                price: Price::usd(5., "Makerbase"),
+            },
+            Self {
+                name: "MKS TMC2226",
+                max_current: 2.5,
+                voltage: 4.75, 29.,
+                max_microsteps: 256,
+                max_step_rate_khz: 6000.,
+                price: Price::usd(6., "Makerbase"),
+            },
+            Self {
+                name: "MKS TMC2160",
+                max_current: 4.33,
+                voltage: 9., 60.,
+                max_microsteps: 256,
+                max_step_rate_khz: 6000.,
+                price: Price::usd(16., "Makerbase"),
+            },
+            Self {
+                name: "MKS TMC2160-OC",
+                max_current: 4.33,
+                voltage: 9., 60.,
+                max_microsteps: 256,
+                max_step_rate_khz: 6000.,
+                price: Price::usd(19., "Makerbase"),
+            },
+        ]
+    }
+
+    /// Find a driver from the catalogue by name
+    pub fn by_name(name: &str) -> Self {
+        Self::drivers()
+            .into_iter()
+            .find(|driver| driver.name == name)
+            .unwrap()
+    }
+}
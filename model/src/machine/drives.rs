@@ -0,0 +1,155 @@
+use std::f64::consts::TAU;
+
+use crate::physics::{
+    Acceleration, Force, Length, Mass, MomentOfInertia, Speed, Torque, Voltage,
+    STANDARD_GRAVITY,
+};
+
+use super::{
+    axes::Axis, drivers::StepperDriver, motors::StepperMotor, rails,
+    screws::BallScrew,
+};
+
+/// The drive of a linear axis: stepper driver, stepper motor, coupling, and
+/// ball screw
+#[derive(Debug)]
+pub struct AxisDrive {
+    pub axis: Axis,
+    pub driver: StepperDriver,
+    pub microsteps: u32,
+    pub motor: StepperMotor,
+    pub screw: BallScrew,
+    pub screw_length: Length,
+    pub moving_mass: Mass,
+
+    /// The safety margin required between pull-out and required torque
+    ///
+    /// Stepper motor datasheets tend to be optimistic, and our pull-out torque
+    /// model is too. A factor of 2 seems to be a common rule of thumb.
+    pub safety_factor: f64,
+}
+
+impl AxisDrive {
+    /// The maximum recommended ratio of load inertia to rotor inertia
+    ///
+    /// Recommendations for stepper motors seem to vary between 5 and 10.
+    pub const MAX_INERTIA_RATIO: f64 = 10.;
+
+    /// The distance the axis moves per microstep
+    pub fn microstep_resolution(&self) -> Length {
+        self.screw.lead
+            / (StepperMotor::FULL_STEPS_PER_REVOLUTION
+                * f64::from(self.microsteps))
+    }
+
+    /// The number of microsteps per millimeter of axis movement
+    pub fn steps_per_mm(&self) -> f64 {
+        1. / self.microstep_resolution().value_mm()
+    }
+
+    /// The axial force on the ball screw
+    pub fn axial_force(
+        &self,
+        cutting_force: Force,
+        acceleration: Acceleration,
+    ) -> Force {
+        let weight = self.moving_mass * STANDARD_GRAVITY;
+
+        // The weight of the moving mass loads the rails of the horizontal
+        // axes, which causes friction. For the vertical axis, the ball screw
+        // has to carry the weight directly. The rails there are loaded by
+        // the cutting forces, but let's ignore that for now.
+        let (friction, gravity) = match self.axis {
+            Axis::X | Axis::Y => (
+                weight * rails::friction_coefficient(),
+                Force::from_value_n(0.),
+            ),
+            Axis::Z => (Force::from_value_n(0.), weight),
+        };
+
+        let inertial_force = self.moving_mass * acceleration;
+
+        cutting_force + friction + gravity + inertial_force
+    }
+
+    /// The moment of inertia of the load, as seen by the motor
+    ///
+    /// This includes the ball screw, the coupling, and the reflected inertia
+    /// of the moving mass.
+    pub fn load_inertia(&self) -> MomentOfInertia {
+        let radius_of_gyration = self.screw.lead.value_m() / TAU;
+        let moving_mass = MomentOfInertia::from_value_kg_m2(
+            self.moving_mass.value_kg() * radius_of_gyration.powi(2),
+        );
+
+        self.screw.moment_of_inertia(self.screw_length)
+            + coupling_inertia()
+            + moving_mass
+    }
+
+    /// The ratio of load inertia to rotor inertia
+    pub fn inertia_ratio(&self) -> f64 {
+        self.load_inertia().value_kg_m2()
+            / self.motor.rotor_inertia.value_kg_m2()
+    }
+
+    /// The motor torque required to drive the axis
+    pub fn required_torque(
+        &self,
+        cutting_force: Force,
+        acceleration: Acceleration,
+    ) -> Torque {
+        let lead = self.screw.lead.value_m();
+
+        let load_torque =
+            self.axial_force(cutting_force, acceleration).value_n() * lead
+                / (TAU * self.screw.efficiency);
+
+        // The inertia of the moving mass is already accounted for in the
+        // axial force. What's left are the rotating parts.
+        let rotating_inertia = self.motor.rotor_inertia
+            + self.screw.moment_of_inertia(self.screw_length)
+            + coupling_inertia();
+        let angular_acceleration = acceleration.value_m_per_s2() / lead * TAU;
+        let acceleration_torque =
+            rotating_inertia.value_kg_m2() * angular_acceleration;
+
+        Torque::from_value_nm(load_torque + acceleration_torque)
+    }
+
+    /// The torque the motor can deliver when moving the axis at this speed
+    pub fn available_torque(
+        &self,
+        speed: Speed,
+        supply_voltage: Voltage,
+    ) -> Torque {
+        self.motor
+            .pull_out_torque(self.screw.rotational_speed(speed), supply_voltage)
+    }
+
+    /// The ratio of available to required torque at the given speed
+    pub fn torque_margin(
+        &self,
+        speed: Speed,
+        cutting_force: Force,
+        acceleration: Acceleration,
+        supply_voltage: Voltage,
+    ) -> f64 {
+        self.available_torque(speed, supply_voltage).value_nm()
+            / self.required_torque(cutting_force, acceleration).value_nm()
+    }
+}
+
+/// The moment of inertia of the coupling between motor and ball screw
+fn coupling_inertia() -> MomentOfInertia {
+    // A typical aluminium jaw coupling with 25 mm diameter and 30 mm length
+    // comes to about that, treating it as a solid cylinder.
+    MomentOfInertia::from_value_g_cm2(31.)
+}
+
+/// The acceleration that the axes are designed for
+pub fn design_acceleration() -> Acceleration {
+    // Hobby machines of this class seem to be typically configured for
+    // somewhere around 0.5 m/s², which I think is a reasonable target.
+    Acceleration::from_value_m_per_s2(0.5)
+}
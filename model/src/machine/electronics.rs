@@ -0,0 +1,175 @@
+use std::fmt;
+
+use crate::physics::{Current, Length, Power, Voltage};
+
+use super::{axes::Axis, drives::AxisDrive, power_supply::PowerSupply};
+
+/// The electronics that power and control the axis drives
+pub struct Electronics {
+    pub power_supply: PowerSupply,
+
+    /// The power consumed by the controller board and its peripherals
+    pub controller_power: Power,
+}
+
+impl Electronics {
+    /// The coarsest acceptable resolution of an axis
+    pub fn target_resolution() -> Length {
+        Length::from_value_mm(0.01)
+    }
+
+    /// Check the axis drives against the electronics
+    pub fn check(&self, drives: &[AxisDrive]) -> Vec<Problem> {
+        let mut problems = Vec::new();
+
+        for drive in drives {
+            let axis = drive.axis;
+            let driver = &drive.driver;
+            let motor = &drive.motor;
+
+            if motor.rated_current > driver.max_current {
+                problems.push(Problem::DriverCurrentTooLow {
+                    axis,
+                    rated_current: motor.rated_current,
+                    max_current: driver.max_current,
+                });
+            }
+
+            let voltage = self.power_supply.voltage;
+            if voltage < driver.min_voltage || voltage > driver.max_voltage {
+                problems.push(Problem::SupplyVoltageOutOfRange {
+                    axis,
+                    voltage,
+                    min_voltage: driver.min_voltage,
+                    max_voltage: driver.max_voltage,
+                });
+            }
+
+            if !drive.microsteps.is_power_of_two()
+                || drive.microsteps > driver.max_microsteps
+            {
+                problems.push(Problem::MicrostepsNotSupported {
+                    axis,
+                    microsteps: drive.microsteps,
+                    max_microsteps: driver.max_microsteps,
+                });
+            }
+
+            let resolution = drive.microstep_resolution();
+            if resolution > Self::target_resolution() {
+                problems
+                    .push(Problem::ResolutionTooCoarse { axis, resolution });
+            }
+        }
+
+        let required_power = self.required_power(drives);
+        if required_power > self.power_supply.power {
+            problems.push(Problem::PowerSupplyTooWeak {
+                required_power,
+                power: self.power_supply.power,
+            });
+        }
+
+        problems
+    }
+
+    /// The power the power supply needs to deliver
+    ///
+    /// This includes some headroom, so the power supply isn't running at its
+    /// limit all the time.
+    pub fn required_power(&self, drives: &[AxisDrive]) -> Power {
+        // A rule of thumb from Geckodrive: The power supply current needs to
+        // be about 2/3 of the sum of the rated motor currents. Chopper drivers
+        // only draw what the motors actually convert, and not all motors run
+        // at full load at the same time.
+        let motor_current = drives
+            .iter()
+            .map(|drive| drive.motor.rated_current.value_a())
+            .sum::<f64>()
+            * 2.
+            / 3.;
+        let motor_power =
+            self.power_supply.voltage * Current::from_value_a(motor_current);
+
+        // Meanwell recommends not to run their supplies at full load
+        // continuously. Let's go with 25% headroom.
+        (motor_power + self.controller_power) * 1.25
+    }
+}
+
+/// A problem found when checking the axis drives against the electronics
+pub enum Problem {
+    DriverCurrentTooLow {
+        axis: Axis,
+        rated_current: Current,
+        max_current: Current,
+    },
+    SupplyVoltageOutOfRange {
+        axis: Axis,
+        voltage: Voltage,
+        min_voltage: Voltage,
+        max_voltage: Voltage,
+    },
+    MicrostepsNotSupported {
+        axis: Axis,
+        microsteps: u32,
+        max_microsteps: u32,
+    },
+    ResolutionTooCoarse {
+        axis: Axis,
+        resolution: Length,
+    },
+    PowerSupplyTooWeak {
+        required_power: Power,
+        power: Power,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::DriverCurrentTooLow {
+                axis,
+                rated_current,
+                max_current,
+            } => write!(
+                f,
+                "{axis}-axis: Motor rated current ({rated_current}) exceeds \
+                driver max current ({max_current})",
+            ),
+            Problem::SupplyVoltageOutOfRange {
+                axis,
+                voltage,
+                min_voltage,
+                max_voltage,
+            } => write!(
+                f,
+                "{axis}-axis: Supply voltage ({voltage}) outside of driver \
+                range ({min_voltage} - {max_voltage})",
+            ),
+            Problem::MicrostepsNotSupported {
+                axis,
+                microsteps,
+                max_microsteps,
+            } => write!(
+                f,
+                "{axis}-axis: Driver doesn't support {microsteps} microsteps \
+                (max {max_microsteps})",
+            ),
+            Problem::ResolutionTooCoarse { axis, resolution } => write!(
+                f,
+                "{axis}-axis: Resolution ({:.4} mm) coarser than target \
+                ({:.4} mm)",
+                resolution.value_mm(),
+                Electronics::target_resolution().value_mm(),
+            ),
+            Problem::PowerSupplyTooWeak {
+                required_power,
+                power,
+            } => write!(
+                f,
+                "Power supply ({power}) too weak (required: {required_power})",
+            ),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{
+    machine::{
+        axes::{y, z, Axis},
+        configuration::Configuration,
+        rails::Rail,
+        spindle::{SpindleClamp, SpindleGeometry},
+    },
+    physics::{Length, Mass},
+};
+
+/// The kinematic layout of the machine
+///
+/// The design target is 40x40x40 cm³ outer dimensions. Everything that goes
+/// into the machine takes up some of that, and whatever is left over is
+/// travel.
+#[derive(Clone)]
+pub struct Layout {
+    pub configuration: Configuration,
+
+    /// The outer dimensions of the machine, along x, y, and z
+    pub outer: [Length; 3],
+
+    /// The thickness of the base plate and the columns
+    pub plate_thickness: Length,
+
+    /// The size of the table, along x and y
+    pub table: [Length; 2],
+
+    pub rail: Rail,
+
+    /// The length of a ball screw end support, along its screw
+    pub screw_support_length: Length,
+
+    /// The width of the spindle clamp, along x
+    pub spindle_clamp_width: Length,
+
+    /// The mass of the spindle clamp
+    pub spindle_clamp_mass: Mass,
+
+    /// The total height of the spindle, including its connector
+    pub spindle_height: Length,
+}
+
+impl Layout {
+    pub fn new(
+        configuration: Configuration,
+        rail: Rail,
+        spindle_clamp: &SpindleClamp,
+    ) -> Self {
+        let size = Length::from_value_mm(400.);
+
+        Self {
+            configuration,
+            outer: [size, size, size],
+            plate_thickness: Length::from_value_mm(20.),
+            // See `cad/cnc-mill.scad`.
+            table: [Length::from_value_mm(300.), Length::from_value_mm(150.)],
+            rail,
+            // BK12/BF12, which fit the SFU1605.
+            screw_support_length: Length::from_value_mm(25.),
+            spindle_clamp_width: spindle_clamp.width(),
+            spindle_clamp_mass: spindle_clamp.mass(),
+            spindle_height: SpindleGeometry::new().height_total(),
+        }
+    }
+
+    /// The height of the table surface above the bottom of the machine
+    pub fn table_top(&self) -> Length {
+        match self.configuration {
+            Configuration::FixedGantry => {
+                self.plate_thickness
+                    + self.rail.height_total
+                    + y::table_thickness()
+            }
+            // The table is bolted straight to the base.
+            Configuration::MovingGantry => {
+                self.plate_thickness + y::table_thickness()
+            }
+        }
+    }
+
+    /// The length of the rails of an axis
+    ///
+    /// The x- and y-axis rails run between the screw supports. The z-axis
+    /// rails need to be long enough for the full travel, with the carriages on
+    /// them.
+    pub fn rail_length(&self, axis: Axis) -> Length {
+        let support = self.screw_support_length.value_mm();
+
+        let length_mm = match axis {
+            Axis::X => {
+                self.outer[0].value_mm()
+                    - self.plate_thickness.value_mm() * 2.
+                    - support * 2.
+            }
+            Axis::Y => self.outer[1].value_mm() - support * 2.,
+            Axis::Z => {
+                z::max_travel().value_mm()
+                    + z::carriage_spacing().value_mm()
+                    + self.rail.carriage_length.value_mm()
+            }
+        };
+
+        Length::from_value_mm(length_mm)
+    }
+
+    /// Break down the outer dimension along an axis into what consumes it
+    pub fn envelope(&self, axis: Axis) -> Envelope {
+        let consumers = match axis {
+            // The x-axis moves the spindle clamp between the columns, and its
+            // screw needs a support on each end.
+            Axis::X => vec![
+                ("columns", self.plate_thickness.value_mm() * 2.),
+                ("screw supports", self.screw_support_length.value_mm() * 2.),
+                ("spindle clamp", self.spindle_clamp_width.value_mm()),
+            ],
+            // The table moves along the base, and can't run into the screw
+            // supports at either end.
+            Axis::Y if self.configuration == Configuration::FixedGantry => {
+                vec![
+                    ("table", self.table[1].value_mm()),
+                    (
+                        "screw supports",
+                        self.screw_support_length.value_mm() * 2.,
+                    ),
+                ]
+            }
+            // The gantry moves along the base instead, and the same goes for
+            // it. The table doesn't limit the travel anymore.
+            Axis::Y => vec![
+                ("gantry", y::gantry_depth().value_mm()),
+                ("screw supports", self.screw_support_length.value_mm() * 2.),
+            ],
+            // With the spindle at the top, it must still fit into the outer
+            // dimensions. From there, it can go down until it hits the table.
+            Axis::Z => {
+                let mut consumers =
+                    vec![("base plate", self.plate_thickness.value_mm())];
+                if self.configuration == Configuration::FixedGantry {
+                    consumers.push((
+                        "y-axis rails",
+                        self.rail.height_total.value_mm(),
+                    ));
+                }
+                consumers.extend([
+                    ("table", y::table_thickness().value_mm()),
+                    ("spindle", self.spindle_height.value_mm()),
+                ]);
+                consumers
+            }
+        };
+
+        let outer = match axis {
+            Axis::X => self.outer[0],
+            Axis::Y => self.outer[1],
+            Axis::Z => self.outer[2],
+        };
+
+        Envelope {
+            axis,
+            outer,
+            consumers: consumers
+                .into_iter()
+                .map(|(name, length_mm)| {
+                    (name, Length::from_value_mm(length_mm))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// How the outer dimension along an axis is used up
+///
+/// See [`Layout::envelope`].
+pub struct Envelope {
+    pub axis: Axis,
+    pub outer: Length,
+    pub consumers: Vec<(&'static str, Length)>,
+}
+
+impl Envelope {
+    /// The travel that's left over
+    pub fn travel(&self) -> Length {
+        let consumed: f64 = self
+            .consumers
+            .iter()
+            .map(|(_, length)| length.value_mm())
+            .sum();
+
+        Length::from_value_mm(self.outer.value_mm() - consumed)
+    }
+}
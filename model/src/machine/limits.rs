@@ -0,0 +1,225 @@
+use std::fmt::{self, Write as _};
+
+use crate::physics::{
+    Acceleration, Force, Frequency, RotationalSpeed, Speed, Voltage,
+};
+
+use super::{axes::Axis, drives::AxisDrive, motors::StepperMotor};
+
+/// The fraction of the torque-limited speed that rapids are limited to
+const RAPID_TORQUE_RESERVE: f64 = 0.8;
+
+/// The highest step rate that the controller can generate for an axis
+///
+/// That's about 30 kHz for grbl on an ATmega328P (Arduino Uno). The drivers
+/// can take a lot more than that.
+const CONTROLLER_MAX_STEP_RATE: Frequency = Frequency::from_value_hz(30_000.);
+
+/// The motion limits of an axis, as derived from its drive
+pub struct AxisLimits {
+    pub axis: Axis,
+    pub steps_per_mm: f64,
+
+    /// The maximum velocity while cutting, under worst-case cutting force
+    pub max_feed_velocity: VelocityLimit,
+
+    /// The maximum velocity while not cutting
+    pub max_rapid_velocity: VelocityLimit,
+
+    /// The maximum acceleration, valid up to the max rapid velocity
+    pub max_acceleration: Acceleration,
+}
+
+impl AxisLimits {
+    pub fn new(
+        drive: &AxisDrive,
+        cutting_force: Force,
+        supply_voltage: Voltage,
+    ) -> Self {
+        let max_feed_velocity =
+            max_velocity(drive, cutting_force, supply_voltage);
+        let mut max_rapid_velocity =
+            max_velocity(drive, Force::from_value_n(0.), supply_voltage);
+
+        // Controllers like grbl use a single acceleration value per axis. The
+        // motor has the least torque available at the highest speed, so
+        // that's where we need to determine the acceleration.
+        //
+        // If that's where motor torque runs out, there's none left to
+        // accelerate with. Let's back off a bit, to leave some.
+        if let LimitedBy::MotorTorque = max_rapid_velocity.limited_by {
+            max_rapid_velocity.speed = Speed::from_value_mm_per_min(
+                max_rapid_velocity.speed.value_mm_per_min()
+                    * RAPID_TORQUE_RESERVE,
+            );
+        }
+        let max_acceleration =
+            max_acceleration(drive, max_rapid_velocity.speed, supply_voltage);
+
+        Self {
+            axis: drive.axis,
+            steps_per_mm: drive.steps_per_mm(),
+            max_feed_velocity,
+            max_rapid_velocity,
+            max_acceleration,
+        }
+    }
+}
+
+/// A maximum velocity, and what limits it
+pub struct VelocityLimit {
+    pub speed: Speed,
+    pub limited_by: LimitedBy,
+}
+
+impl fmt::Display for VelocityLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (limited by {})", self.speed, self.limited_by)
+    }
+}
+
+/// The factor limiting a velocity
+pub enum LimitedBy {
+    MotorTorque,
+    ScrewSpeed,
+    DriverStepRate,
+    ControllerStepRate,
+}
+
+impl fmt::Display for LimitedBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LimitedBy::MotorTorque => write!(f, "motor torque"),
+            LimitedBy::ScrewSpeed => write!(f, "ball screw speed"),
+            LimitedBy::DriverStepRate => write!(f, "driver step rate"),
+            LimitedBy::ControllerStepRate => {
+                write!(f, "controller step rate")
+            }
+        }
+    }
+}
+
+/// Format the limits as grbl settings
+///
+/// Those are `$100`-`$102` (steps/mm), `$110`-`$112` (max rate, mm/min), and
+/// `$120`-`$122` (acceleration, mm/s²).
+pub fn grbl_settings(limits: &[AxisLimits]) -> String {
+    let mut settings = String::new();
+
+    for l in limits {
+        let i = axis_index(l.axis);
+        writeln!(settings, "$10{i}={:.3}", l.steps_per_mm).unwrap();
+    }
+    for l in limits {
+        let i = axis_index(l.axis);
+        let rate = l.max_rapid_velocity.speed.value_mm_per_min();
+        writeln!(settings, "$11{i}={rate:.0}").unwrap();
+    }
+    for l in limits {
+        let i = axis_index(l.axis);
+        let acceleration = l.max_acceleration.value_m_per_s2() * 1000.;
+        writeln!(settings, "$12{i}={acceleration:.0}").unwrap();
+    }
+
+    settings
+}
+
+fn axis_index(axis: Axis) -> u8 {
+    match axis {
+        Axis::X => 0,
+        Axis::Y => 1,
+        Axis::Z => 2,
+    }
+}
+
+fn max_velocity(
+    drive: &AxisDrive,
+    load: Force,
+    supply_voltage: Voltage,
+) -> VelocityLimit {
+    let screw_speed = drive.screw.max_rotational_speed(drive.screw_length);
+
+    let (step_rate, step_rate_limited_by) =
+        if CONTROLLER_MAX_STEP_RATE < drive.driver.max_step_rate {
+            (CONTROLLER_MAX_STEP_RATE, LimitedBy::ControllerStepRate)
+        } else {
+            (drive.driver.max_step_rate, LimitedBy::DriverStepRate)
+        };
+    let step_rate_speed = RotationalSpeed::from_value_rpm(
+        step_rate.value_hz()
+            / (StepperMotor::FULL_STEPS_PER_REVOLUTION
+                * f64::from(drive.microsteps))
+            * 60.,
+    );
+
+    let (max_speed, limited_by) = if screw_speed < step_rate_speed {
+        (screw_speed, LimitedBy::ScrewSpeed)
+    } else {
+        (step_rate_speed, step_rate_limited_by)
+    };
+
+    let required_torque = drive
+        .required_torque(load, Acceleration::from_value_m_per_s2(0.))
+        .value_nm()
+        * drive.safety_factor;
+    let is_sufficient = |speed: RotationalSpeed| {
+        drive
+            .motor
+            .pull_out_torque(speed, supply_voltage)
+            .value_nm()
+            >= required_torque
+    };
+
+    if is_sufficient(max_speed) {
+        return VelocityLimit {
+            speed: drive.screw.linear_speed(max_speed),
+            limited_by,
+        };
+    }
+
+    // Pull-out torque decreases with speed, so we can find the speed where it
+    // meets the required torque by bisection.
+    let mut low = 0.;
+    let mut high = max_speed.value_rpm();
+    for _ in 0..50 {
+        let mid = (low + high) / 2.;
+        if is_sufficient(RotationalSpeed::from_value_rpm(mid)) {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    VelocityLimit {
+        speed: drive
+            .screw
+            .linear_speed(RotationalSpeed::from_value_rpm(low)),
+        limited_by: LimitedBy::MotorTorque,
+    }
+}
+
+fn max_acceleration(
+    drive: &AxisDrive,
+    speed: Speed,
+    supply_voltage: Voltage,
+) -> Acceleration {
+    // The required torque is linear in acceleration, so we can get the
+    // static part and the part per unit of acceleration, and solve for the
+    // acceleration that uses up the available torque.
+    let no_force = Force::from_value_n(0.);
+    let static_torque = drive
+        .required_torque(no_force, Acceleration::from_value_m_per_s2(0.))
+        .value_nm();
+    let torque_per_acceleration = drive
+        .required_torque(no_force, Acceleration::from_value_m_per_s2(1.))
+        .value_nm()
+        - static_torque;
+
+    let available_torque =
+        drive.available_torque(speed, supply_voltage).value_nm()
+            / drive.safety_factor;
+
+    Acceleration::from_value_m_per_s2(
+        ((available_torque - static_torque) / torque_per_acceleration).max(0.),
+    )
+}
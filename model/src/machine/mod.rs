@@ -1,3 +1,14 @@
 pub mod axes;
+pub mod configuration;
+pub mod drivers;
+pub mod drives;
+pub mod electronics;
+pub mod layout;
+pub mod limits;
+pub mod motors;
+pub mod power_supply;
 pub mod rails;
+pub mod resolution;
+pub mod screws;
 pub mod spindle;
+pub mod stack_up;
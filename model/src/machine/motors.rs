@@ -0,0 +1,215 @@
+use std::fmt;
+
+use crate::{
+    cost::Price,
+    physics::{
+        Current, Inductance, Mass, MomentOfInertia, Resistance,
+        RotationalSpeed, Torque, Voltage,
+    },
+};
+
+/// A hybrid stepper motor, as used for the axes
+#[derive(Debug)]
+pub struct StepperMotor {
+    pub name: &'static str,
+    pub frame: Frame,
+    pub holding_torque: Torque,
+    pub rated_current: Current,
+    pub phase_resistance: Resistance,
+    pub phase_inductance: Inductance,
+    pub rotor_inertia: MomentOfInertia,
+    pub mass: Mass,
+    pub price: Price,
+}
+
+impl StepperMotor {
+    /// The number of full steps per revolution
+    ///
+    /// All motors in the catalogue have a step angle of 1.8°.
+    pub const FULL_STEPS_PER_REVOLUTION: f64 = 200.;
+
+    pub fn motors() -> Vec<Self> {
+        // This is a selection of common motors in the size range that makes
+        // sense for a machine like this. Values are taken from the datasheets
+        // at StepperOnline, as those are easy to find and pretty complete:
+        // https://www.omc-stepperonline.com/
+        //
+        // They also ship from a European warehouse, so that's where the
+        // prices are from.
+
+        macro_rules! motors {
+            ($(
+                Self {
+                    name: $name:expr,
+                    frame: $frame:expr,
+                    holding_torque: $holding_torque:expr,
+                    rated_current: $rated_current:expr,
+                    phase_resistance: $phase_resistance:expr,
+                    phase_inductance: $phase_inductance:expr,
+                    rotor_inertia: $rotor_inertia:expr,
+                    mass: $mass:expr,
+                    price: $price:expr,
+                },
+            )*) => {
+                vec![
+                    $(
+                        Self {
+                            name: $name,
+                            frame: $frame,
+                            holding_torque: Torque::from_value_nm(
+                                $holding_torque,
+                            ),
+                            rated_current: Current::from_value_a(
+                                $rated_current,
+                            ),
+                            phase_resistance: Resistance::from_value_ohm(
+                                $phase_resistance,
+                            ),
+                            phase_inductance: Inductance::from_value_mh(
+                                $phase_inductance,
+                            ),
+                            rotor_inertia: MomentOfInertia::from_value_g_cm2(
+                                $rotor_inertia,
+                            ),
+                            mass: Mass::from_value_kg($mass),
+                            price: $price,
+                        },
+                    )*
+                ]
+            };
+        }
+
+        motors![
+            Self {
+                name: "17HS19-2004S1",
+                frame: Frame::Nema17,
+                holding_torque: 0.59,
+                rated_current: 2.0,
+                phase_resistance: 1.4,
+                phase_inductance: 3.0,
+                rotor_inertia: 82.,
+                mass: 0.5,
+                price: Price::eur(13., "StepperOnline"),
+            },
+            Self {
+                name: "17HS24-2104S",
+                frame: Frame::Nema17,
+                holding_torque: 0.65,
+                rated_current: 2.1,
+                phase_resistance: 1.6,
+                phase_inductance: 3.0,
+                rotor_inertia: 102.,
+                mass: 0.6,
+                price: Price::eur(15., "StepperOnline"),
+            },
+            Self {
+                name: "23HS22-2804S",
+                frame: Frame::Nema23,
+                holding_torque: 1.26,
+                rated_current: 2.8,
+                phase_resistance: 0.9,
+                phase_inductance: 2.5,
+                rotor_inertia: 300.,
+                mass: 0.7,
+                price: Price::eur(23., "StepperOnline"),
+            },
+            Self {
+                name: "23HS30-2804S",
+                frame: Frame::Nema23,
+                holding_torque: 1.9,
+                rated_current: 2.8,
+                phase_resistance: 1.13,
+                phase_inductance: 5.4,
+                rotor_inertia: 440.,
+                mass: 1.0,
+                price: Price::eur(29., "StepperOnline"),
+            },
+            Self {
+                name: "23HS45-4204S",
+                frame: Frame::Nema23,
+                holding_torque: 3.0,
+                rated_current: 4.2,
+                phase_resistance: 0.9,
+                phase_inductance: 3.8,
+                rotor_inertia: 820.,
+                mass: 1.4,
+                price: Price::eur(36., "StepperOnline"),
+            },
+            Self {
+                name: "24HS39-4204D",
+                frame: Frame::Nema24,
+                holding_torque: 4.0,
+                rated_current: 4.2,
+                phase_resistance: 0.7,
+                phase_inductance: 3.2,
+                rotor_inertia: 900.,
+                mass: 1.5,
+                price: Price::eur(42., "StepperOnline"),
+            },
+        ]
+    }
+
+    /// Find a motor from the catalogue by name
+    pub fn by_name(name: &str) -> Self {
+        Self::motors()
+            .into_iter()
+            .find(|motor| motor.name == name)
+            .unwrap()
+    }
+
+    /// Calculate the pull-out torque at the given speed
+    ///
+    /// This is the maximum torque the motor can deliver at that speed, without
+    /// losing steps.
+    pub fn pull_out_torque(
+        &self,
+        speed: RotationalSpeed,
+        supply_voltage: Voltage,
+    ) -> Torque {
+        // A two-phase hybrid stepper motor moves one rotor tooth per 4 full
+        // steps. For a step angle of 1.8°, that's 50 rotor teeth, which means
+        // one mechanical revolution equals 50 electrical periods.
+        let electrical_periods_per_revolution =
+            Self::FULL_STEPS_PER_REVOLUTION / 4.;
+        let electrical_speed =
+            speed.value_rad_per_s() * electrical_periods_per_revolution;
+
+        // The driver can push the rated current through the coils at low
+        // speeds. As the speed increases, the impedance of the coils goes up,
+        // and at some point, the supply voltage is no longer enough to do that.
+        //
+        // This ignores back-EMF, which makes it somewhat optimistic. I think
+        // that's acceptable for comparing motors though, especially since
+        // we're going to check against this curve with a safety margin.
+        let resistance = self.phase_resistance.value_ohm();
+        let reactance = electrical_speed * self.phase_inductance.value_h();
+        let impedance = (resistance.powi(2) + reactance.powi(2)).sqrt();
+
+        let current = (supply_voltage.value_v() / impedance)
+            .min(self.rated_current.value_a());
+
+        // Torque is roughly proportional to current, below saturation.
+        Torque::from_value_nm(
+            self.holding_torque.value_nm() * current
+                / self.rated_current.value_a(),
+        )
+    }
+}
+
+/// The standardized frame size of a stepper motor
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Frame {
+    Nema17,
+    Nema23,
+    Nema24,
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Frame::Nema17 => write!(f, "NEMA 17"),
+            Frame::Nema23 => write!(f, "NEMA 23"),
+            Frame::Nema24 => write!(f, "NEMA 24"),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    cost::Price,
+    physics::{Power, Voltage},
+};
+
+/// A DC power supply for the stepper drivers and controller
+#[derive(Debug)]
+pub struct PowerSupply {
+    pub name: &'static str,
+    pub voltage: Voltage,
+    pub power: Power,
+    pub price: Price,
+}
+
+impl PowerSupply {
+    pub fn power_supplies() -> Vec<Self> {
+        // Meanwell LRS series. Those are the enclosed ones that show up in lots
+        // of kits, and they're cheap and easy to get in Europe.
+        // Prices are from Reichelt.
+
+        macro_rules! power_supplies {
+            ($(
+                Self {
+                    name: $name:expr,
+                    voltage: $voltage:expr,
+                    power: $power:expr,
+                    price: $price:expr,
+                },
+            )*) => {
+                vec![
+                    $(
+                        Self {
+                            name: $name,
+                            voltage: Voltage::from_value_v($voltage),
+                            power: Power::from_value_w($power),
+                            price: $price,
+                        },
+                    )*
+                ]
+            };
+        }
+
+        power_supplies![
+            Self {
+                name: "Meanwell LRS-150-24",
+                voltage: 24.,
+                power: 156.,
+                price: Price::eur(23., "Reichelt"),
+            },
+            Self {
+                name: "Meanwell LRS-200-24",
+                voltage: 24.,
+                power: 211.2,
+                price: Price::eur(27., "Reichelt"),
+            },
+            Self {
+                name: "Meanwell LRS-350-24",
+                voltage: 24.,
+                power: 350.4,
+                price: Price::eur(34., "Reichelt"),
+            },
+            Self {
+                name: "Meanwell LRS-350-36",
+                voltage: 36.,
+                power: 352.8,
+                price: Price::eur(35., "Reichelt"),
+            },
+            Self {
+                name: "Meanwell LRS-450-48",
+                voltage: 48.,
+                power: 451.2,
+                price: Price::eur(46., "Reichelt"),
+            },
+        ]
+    }
+
+    /// Find a power supply from the catalogue by name
+    pub fn by_name(name: &str) -> Self {
+        Self::power_supplies()
+            .into_iter()
+            .find(|power_supply| power_supply.name == name)
+            .unwrap()
+    }
+
+    /// Find the smallest power supply with the given voltage and enough power
+    pub fn recommend(voltage: Voltage, power: Power) -> Option<Self> {
+        Self::power_supplies()
+            .into_iter()
+            .filter(|power_supply| {
+                power_supply.voltage == voltage && power_supply.power >= power
+            })
+            .reduce(|a, b| if a.power <= b.power { a } else { b })
+    }
+}
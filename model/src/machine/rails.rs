@@ -1,7 +1,200 @@
-use crate::physics::Length;
+use crate::{
+    cost::Price,
+    physics::{Force, Length, Stiffness},
+};
 
-/// Let's just assume MGN15 for now, unless it turns out not to be sufficient.
-pub fn mgn15_height_total() -> Length {
-    // See HIWIN catalogue table 3.79 on page 97/
-    Length::from_value_mm(16.)
+/// A profiled linear guide, as used for all axes
+#[derive(Clone, Debug)]
+pub struct Rail {
+    pub name: &'static str,
+
+    /// The width and height of the rail itself
+    pub rail_width: Length,
+    pub rail_height: Length,
+
+    /// The height from the bottom of the rail to the top of the carriage
+    pub height_total: Length,
+
+    pub carriage_width: Length,
+    pub carriage_length: Length,
+
+    /// The stiffness of a single carriage against a force perpendicular to
+    /// the rail
+    pub carriage_stiffness: Stiffness,
+
+    /// The static load rating (C₀) of a single carriage
+    pub static_load_rating: Force,
+
+    pub price_per_meter: Price,
+    pub carriage_price: Price,
+}
+
+impl Rail {
+    pub fn rails() -> Vec<Self> {
+        // Dimensions are from the HIWIN catalogue (MGN15: table 3.79 on page
+        // 97). The catalogue only gives rigidity tables for some sizes, and
+        // cheap guides won't come with a defined preload anyway, so the
+        // stiffness values are rough estimates for light preload. The load
+        // ratings are HIWIN's too, and the cheap guides probably won't reach
+        // them.
+        //
+        // HIWIN is out of the question, price-wise. Prices are for the
+        // unbranded guides from Bulk Man 3D, which sells rails by length and
+        // carriages separately.
+
+        macro_rules! rails {
+            ($(
+                Self {
+                    name: $name:expr,
+                    rail_width: $rail_width:expr,
+                    rail_height: $rail_height:expr,
+                    height_total: $height_total:expr,
+                    carriage_width: $carriage_width:expr,
+                    carriage_length: $carriage_length:expr,
+                    carriage_stiffness: $carriage_stiffness:expr,
+                    static_load_rating_kn: $static_load_rating_kn:expr,
+                    price_per_meter: $price_per_meter:expr,
+                    carriage_price: $carriage_price:expr,
+                },
+            )*) => {
+                vec![
+                    $(
+                        Self {
+                            name: $name,
+                            rail_width: Length::from_value_mm($rail_width),
+                            rail_height: Length::from_value_mm($rail_height),
+                            height_total: Length::from_value_mm($height_total),
+                            carriage_width: Length::from_value_mm(
+                                $carriage_width,
+                            ),
+                            carriage_length: Length::from_value_mm(
+                                $carriage_length,
+                            ),
+                            carriage_stiffness: Stiffness::from_value_n_per_um(
+                                $carriage_stiffness,
+                            ),
+                            static_load_rating: Force::from_value_n(
+                                $static_load_rating_kn * 1000.,
+                            ),
+                            price_per_meter: $price_per_meter,
+                            carriage_price: $carriage_price,
+                        },
+                    )*
+                ]
+            };
+        }
+
+        rails![
+            Self {
+                name: "MGN12H",
+                rail_width: 12.,
+                rail_height: 8.,
+                height_total: 13.,
+                carriage_width: 27.,
+                carriage_length: 45.4,
+                carriage_stiffness: 60.,
+                static_load_rating_kn: 5.88,
+                price_per_meter: Price::usd(28., "Bulk Man 3D"),
+                carriage_price: Price::usd(9., "Bulk Man 3D"),
+            },
+            Self {
+                name: "MGN15H",
+                rail_width: 15.,
+                rail_height: 10.,
+                height_total: 16.,
+                carriage_width: 32.,
+                carriage_length: 58.8,
+                carriage_stiffness: 90.,
+                static_load_rating_kn: 9.11,
+                price_per_meter: Price::usd(36., "Bulk Man 3D"),
+                carriage_price: Price::usd(13., "Bulk Man 3D"),
+            },
+            Self {
+                name: "HGH15CA",
+                rail_width: 15.,
+                rail_height: 15.,
+                height_total: 28.,
+                carriage_width: 34.,
+                carriage_length: 61.4,
+                carriage_stiffness: 200.,
+                static_load_rating_kn: 16.97,
+                price_per_meter: Price::usd(40., "Bulk Man 3D"),
+                carriage_price: Price::usd(17., "Bulk Man 3D"),
+            },
+            Self {
+                name: "HGH20CA",
+                rail_width: 20.,
+                rail_height: 17.5,
+                height_total: 30.,
+                carriage_width: 44.,
+                carriage_length: 77.5,
+                carriage_stiffness: 260.,
+                static_load_rating_kn: 27.76,
+                price_per_meter: Price::usd(52., "Bulk Man 3D"),
+                carriage_price: Price::usd(24., "Bulk Man 3D"),
+            },
+        ]
+    }
+
+    /// Find a rail from the catalogue by name
+    pub fn by_name(name: &str) -> Self {
+        Self::rails()
+            .into_iter()
+            .find(|rail| rail.name == name)
+            .unwrap()
+    }
+
+    /// The load on the most highly loaded carriage of an axis
+    ///
+    /// Same arrangement as in [`Rail::axis_stiffness`]. The force is shared
+    /// by all 4 carriages, and the tilting moment from the lever arm adds to
+    /// the load of one carriage pair.
+    pub fn carriage_load(
+        &self,
+        force: Force,
+        lever_arm: Length,
+        spacing: Length,
+    ) -> Force {
+        let f = force.value_n();
+        let moment = f * lever_arm.value_m();
+
+        Force::from_value_n(f / 4. + moment / spacing.value_m() / 2.)
+    }
+
+    /// The static safety factor of a carriage under the given load
+    pub fn static_safety_factor(&self, carriage_load: Force) -> f64 {
+        self.static_load_rating.value_n() / carriage_load.value_n()
+    }
+
+    /// The stiffness of an axis, at a point offset from its carriages
+    ///
+    /// Assumes the usual arrangement of two rails with two carriages each.
+    /// The force acts perpendicular to the rails, at `lever_arm` from the
+    /// center of the carriages. It tilts the carriage assembly, which is
+    /// resisted by carriage pairs that are `spacing` apart.
+    pub fn axis_stiffness(
+        &self,
+        lever_arm: Length,
+        spacing: Length,
+    ) -> Stiffness {
+        let k = self.carriage_stiffness.value_n_per_m();
+        let h = lever_arm.value_m();
+        let s = spacing.value_m();
+
+        // All four carriages share the force. The moment loads each pair
+        // with `F * h / s`, in opposite directions, which tilts the assembly
+        // by `F * h / (k * s²)`.
+        let translation = 1. / (4. * k);
+        let tilt = h.powi(2) / (k * s.powi(2));
+
+        Stiffness::from_compliance_m_per_n(translation + tilt)
+    }
+}
+
+/// The friction coefficient of the linear guides
+pub fn friction_coefficient() -> f64 {
+    // The HIWIN catalogue lists 0.002 to 0.003 for its guides, not including
+    // the seals. Let's be a bit more pessimistic, to account for those (and
+    // cheaper guides).
+    0.005
 }
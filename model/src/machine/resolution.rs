@@ -0,0 +1,150 @@
+use std::{f64::consts::FRAC_PI_2, fmt};
+
+use crate::{
+    physics::{Acceleration, Force, Length, Speed, Voltage},
+    tools::Tool,
+};
+
+use super::{drives::AxisDrive, motors::StepperMotor, spindle::Spindle};
+
+/// The positioning resolution of an axis
+pub struct Resolution {
+    /// The distance the axis moves per full step
+    pub full_step: Length,
+
+    /// The distance the axis moves per microstep, as configured
+    pub microstep: Length,
+
+    /// The number of microsteps per full step that can actually be resolved
+    pub effective_microsteps: u32,
+
+    /// The smallest movement the axis can actually resolve
+    pub effective: Length,
+}
+
+impl Resolution {
+    pub fn new(drive: &AxisDrive) -> Self {
+        let full_step =
+            drive.screw.lead / StepperMotor::FULL_STEPS_PER_REVOLUTION;
+
+        // The torque a motor produces when moving a single microstep is much
+        // lower than its holding torque. For a microstep of 1/m full steps,
+        // it's `holding_torque * sin(90° / m)`. If that's lower than the torque
+        // required to overcome friction (and gravity, for the z-axis), the
+        // motor won't move until enough microsteps have accumulated.
+        let static_torque = drive
+            .required_torque(
+                Force::from_value_n(0.),
+                Acceleration::from_value_m_per_s2(0.),
+            )
+            .value_nm();
+        let holding_torque = drive.motor.holding_torque.value_nm();
+
+        let mut effective_microsteps = drive.microsteps;
+        while effective_microsteps > 1 {
+            let incremental_torque = holding_torque
+                * (FRAC_PI_2 / f64::from(effective_microsteps)).sin();
+            if incremental_torque >= static_torque {
+                break;
+            }
+
+            effective_microsteps /= 2;
+        }
+
+        Self {
+            full_step,
+            microstep: drive.microstep_resolution(),
+            effective_microsteps,
+            effective: full_step / f64::from(effective_microsteps),
+        }
+    }
+}
+
+/// The risk of an axis missing steps at an operating point
+pub struct StallRisk<'r> {
+    pub tool: &'r Tool,
+    pub feed: Speed,
+    pub cutting_force: Force,
+
+    /// The ratio of available to required motor torque
+    pub margin: f64,
+
+    /// The margin the drive requires, see [`AxisDrive::safety_factor`]
+    pub safety_factor: f64,
+}
+
+impl StallRisk<'_> {
+    /// Compute the stall risk for each tool, when cutting at its feed rate
+    pub fn for_tools<'r>(
+        drive: &AxisDrive,
+        tools: &'r [Tool],
+        spindle: &Spindle,
+        acceleration: Acceleration,
+        supply_voltage: Voltage,
+    ) -> Vec<StallRisk<'r>> {
+        tools
+            .iter()
+            .map(|tool| {
+                let feed = tool.feed(spindle.speed(tool.desired_rpm()));
+
+                // Like in the worst case, the tool can't cut harder than the
+                // spindle's torque allows.
+                let (mut cutting_force, tool_torque) =
+                    tool.tangential_cutting_force();
+                let spindle_torque = spindle.torque(tool.desired_rpm());
+                if tool_torque > spindle_torque {
+                    cutting_force = spindle_torque.to_force(tool.diameter);
+                }
+
+                let margin = drive.torque_margin(
+                    feed,
+                    cutting_force,
+                    acceleration,
+                    supply_voltage,
+                );
+
+                StallRisk {
+                    tool,
+                    feed,
+                    cutting_force,
+                    margin,
+                    safety_factor: drive.safety_factor,
+                }
+            })
+            .collect()
+    }
+
+    /// Indicate whether the motor is likely to skip steps
+    pub fn level(&self) -> RiskLevel {
+        if self.margin < 1. {
+            RiskLevel::Stall
+        } else if self.margin < self.safety_factor {
+            RiskLevel::Marginal
+        } else {
+            RiskLevel::Safe
+        }
+    }
+}
+
+/// How likely an axis is to skip steps
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RiskLevel {
+    /// The torque margin is larger than the safety factor
+    Safe,
+
+    /// The motor should be able to make it, but there's not much margin
+    Marginal,
+
+    /// The motor doesn't have enough torque; it will skip steps
+    Stall,
+}
+
+impl fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RiskLevel::Safe => write!(f, "safe"),
+            RiskLevel::Marginal => write!(f, "marginal"),
+            RiskLevel::Stall => write!(f, "WILL SKIP STEPS"),
+        }
+    }
+}
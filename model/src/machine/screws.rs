@@ -0,0 +1,250 @@
+use std::f64::consts::PI;
+
+use crate::{
+    cost::Price,
+    frame::material::Material,
+    physics::{
+        Diameter, Force, Length, MomentOfInertia, RotationalSpeed, Speed,
+        Stiffness,
+    },
+};
+
+/// A ball screw
+#[derive(Debug)]
+pub struct BallScrew {
+    pub name: &'static str,
+    pub diameter: Diameter,
+    pub root_diameter: Diameter,
+    pub lead: Length,
+
+    /// The efficiency of converting torque into axial force
+    pub efficiency: f64,
+
+    /// The basic dynamic load rating (Cₐ)
+    pub dynamic_load_rating: Force,
+
+    /// The end supports that fit the screw, fixed and supported bearing
+    pub end_supports: [&'static str; 2],
+
+    /// The price of the screw shaft, which is cut to length
+    pub price_per_meter: Price,
+
+    /// The prices of the flanged nut and its housing
+    pub nut_prices: [Price; 2],
+
+    /// The prices of the end supports, fixed and supported bearing
+    pub end_support_prices: [Price; 2],
+}
+
+impl BallScrew {
+    pub fn screws() -> Vec<Self> {
+        // These are the rolled ball screws that are widely available from
+        // AliExpress and eBay sellers, in the sizes that make sense for this
+        // machine.
+        //
+        // The HIWIN catalogue (see README) gives an efficiency of around 90%
+        // for ball screws. I don't expect the cheap ones to do quite as well,
+        // but let's go with that for now.
+        //
+        // Dynamic load ratings are from TBI, who make the SFU series most of
+        // them are copies of.
+        //
+        // Root diameters are approximated as nominal diameter minus ball
+        // diameter, as the sellers don't usually specify them.
+        //
+        // The end supports are the BK/BF sets that those sellers offer along
+        // with each screw size. Prices are from Bulk Man 3D, which has all of
+        // it in one place.
+
+        macro_rules! screws {
+            ($(
+                Self {
+                    name: $name:expr,
+                    diameter: $diameter:expr,
+                    root_diameter: $root_diameter:expr,
+                    lead: $lead:expr,
+                    dynamic_load_rating_kn: $dynamic_load_rating_kn:expr,
+                    end_supports: $end_supports:expr,
+                    price_per_meter: $price_per_meter:expr,
+                    nut_prices: $nut_prices:expr,
+                    end_support_prices: $end_support_prices:expr,
+                },
+            )*) => {
+                vec![
+                    $(
+                        Self {
+                            name: $name,
+                            diameter: Diameter::from_length(
+                                Length::from_value_mm($diameter),
+                            ),
+                            root_diameter: Diameter::from_length(
+                                Length::from_value_mm($root_diameter),
+                            ),
+                            lead: Length::from_value_mm($lead),
+                            efficiency: 0.9,
+                            dynamic_load_rating: Force::from_value_n(
+                                $dynamic_load_rating_kn * 1000.,
+                            ),
+                            end_supports: $end_supports,
+                            price_per_meter: $price_per_meter,
+                            nut_prices: $nut_prices,
+                            end_support_prices: $end_support_prices,
+                        },
+                    )*
+                ]
+            };
+        }
+
+        screws![
+            Self {
+                name: "SFU1204",
+                diameter: 12.,
+                root_diameter: 9.6,
+                lead: 4.,
+                dynamic_load_rating_kn: 4.9,
+                end_supports: ["BK10", "BF10"],
+                price_per_meter: Price::usd(30., "Bulk Man 3D"),
+                nut_prices: [
+                    Price::usd(10., "Bulk Man 3D"),
+                    Price::usd(5., "Bulk Man 3D")
+                ],
+                end_support_prices: [
+                    Price::usd(11., "Bulk Man 3D"),
+                    Price::usd(5., "Bulk Man 3D"),
+                ],
+            },
+            Self {
+                name: "SFU1605",
+                diameter: 16.,
+                root_diameter: 12.9,
+                lead: 5.,
+                dynamic_load_rating_kn: 7.65,
+                end_supports: ["BK12", "BF12"],
+                price_per_meter: Price::usd(35., "Bulk Man 3D"),
+                nut_prices: [
+                    Price::usd(12., "Bulk Man 3D"),
+                    Price::usd(6., "Bulk Man 3D")
+                ],
+                end_support_prices: [
+                    Price::usd(14., "Bulk Man 3D"),
+                    Price::usd(6., "Bulk Man 3D"),
+                ],
+            },
+            Self {
+                name: "SFU1610",
+                diameter: 16.,
+                root_diameter: 12.9,
+                lead: 10.,
+                dynamic_load_rating_kn: 9.0,
+                end_supports: ["BK12", "BF12"],
+                price_per_meter: Price::usd(38., "Bulk Man 3D"),
+                nut_prices: [
+                    Price::usd(14., "Bulk Man 3D"),
+                    Price::usd(6., "Bulk Man 3D")
+                ],
+                end_support_prices: [
+                    Price::usd(14., "Bulk Man 3D"),
+                    Price::usd(6., "Bulk Man 3D"),
+                ],
+            },
+            Self {
+                name: "SFU2005",
+                diameter: 20.,
+                root_diameter: 16.9,
+                lead: 5.,
+                dynamic_load_rating_kn: 9.3,
+                end_supports: ["BK15", "BF15"],
+                price_per_meter: Price::usd(45., "Bulk Man 3D"),
+                nut_prices: [
+                    Price::usd(16., "Bulk Man 3D"),
+                    Price::usd(8., "Bulk Man 3D")
+                ],
+                end_support_prices: [
+                    Price::usd(18., "Bulk Man 3D"),
+                    Price::usd(8., "Bulk Man 3D"),
+                ],
+            },
+        ]
+    }
+
+    /// Find a ball screw from the catalogue by name
+    pub fn by_name(name: &str) -> Self {
+        Self::screws()
+            .into_iter()
+            .find(|screw| screw.name == name)
+            .unwrap()
+    }
+
+    /// The rotational speed required to move the nut at the given speed
+    pub fn rotational_speed(&self, speed: Speed) -> RotationalSpeed {
+        RotationalSpeed::from_value_rpm(
+            speed.value_m_per_min() / self.lead.value_m(),
+        )
+    }
+
+    /// The speed at which the nut moves at the given rotational speed
+    pub fn linear_speed(&self, rotational_speed: RotationalSpeed) -> Speed {
+        Speed::from_value_m_per_min(
+            rotational_speed.value_rpm() * self.lead.value_m(),
+        )
+    }
+
+    /// The maximum speed the screw can safely run at, with the given length
+    pub fn max_rotational_speed(&self, length: Length) -> RotationalSpeed {
+        // Critical speed, according to the formula from the HIWIN catalogue.
+        // The factor is for a fixed bearing on one end and a supported bearing
+        // on the other, which is what I'm planning to use. HIWIN recommends
+        // staying below 80% of that.
+        let f = 15.1;
+        let d_r = self.root_diameter.to_length().value_mm();
+        let l = length.value_mm();
+        let critical_speed_rpm = f * d_r / l.powi(2) * 10_000_000.;
+
+        // The HIWIN catalogue also limits the DN value (nominal diameter
+        // times speed) of rolled screws to 50000.
+        let dn_limit_rpm = 50_000. / self.diameter.to_length().value_mm();
+
+        RotationalSpeed::from_value_rpm(
+            (critical_speed_rpm * 0.8).min(dn_limit_rpm),
+        )
+    }
+
+    /// The moment of inertia of a screw of the given length
+    pub fn moment_of_inertia(&self, length: Length) -> MomentOfInertia {
+        // Treating the screw as a solid steel cylinder of its nominal
+        // diameter. That slightly overestimates it, which is fine.
+        let density_steel = 7850.; // kg/m³
+
+        let d = self.diameter.to_length().value_m();
+        let l = length.value_m();
+
+        MomentOfInertia::from_value_kg_m2(
+            PI * density_steel * l * d.powi(4) / 32.,
+        )
+    }
+
+    /// The rated life of the screw, as distance traveled by the nut
+    ///
+    /// This is the life that 90% of screws reach, if the axial force acts
+    /// constantly.
+    pub fn rated_life(&self, axial_force: Force) -> Length {
+        let revolutions = (self.dynamic_load_rating.value_n()
+            / axial_force.value_n())
+        .powi(3)
+            * 1_000_000.;
+
+        Length::from_value_m(revolutions * self.lead.value_m())
+    }
+
+    /// The axial stiffness of the screw shaft, at the given nut position
+    ///
+    /// `length` is the distance between the nut and the fixed bearing, which
+    /// is the one that takes the axial load. This doesn't include the nut and
+    /// the bearings themselves.
+    pub fn axial_stiffness(&self, length: Length) -> Stiffness {
+        let e = Material::Steel.elastic_modulus().value_pa();
+        let a = PI * self.root_diameter.to_length().value_m().powi(2) / 4.;
+
+        Stiffness::from_value_n_per_m(e * a / length.value_m())
+    }
+}
@@ -1,20 +1,331 @@
-use crate::physics::{Power, RotationalSpeed, Torque};
+use std::{error::Error, f64::consts::PI};
+
+use crate::{
+    cost::Price,
+    physics::{Diameter, Length, Mass, Power, RotationalSpeed, Torque},
+    stl::{MassProperties, Mesh},
+};
 
 pub struct Spindle {
     power: Power,
+
+    /// The speed range, as listed by the sellers of the spindle
+    pub min_speed: RotationalSpeed,
+    pub max_speed: RotationalSpeed,
 }
 
 impl Spindle {
-    const MIN: RotationalSpeed = RotationalSpeed::from_value_rpm(5000.);
-    const MAX: RotationalSpeed = RotationalSpeed::from_value_rpm(24000.);
+    pub fn new(
+        power: Power,
+        min_speed: RotationalSpeed,
+        max_speed: RotationalSpeed,
+    ) -> Self {
+        Self {
+            power,
+            min_speed,
+            max_speed,
+        }
+    }
+
+    pub fn power(&self) -> Power {
+        self.power
+    }
+
+    /// The prices of spindle, VFD, and spindle clamp
+    ///
+    /// motor-mall sells both spindle and VFD, and ships them from Europe. The
+    /// clamp is the one from Sorotec (see README).
+    pub fn prices() -> [Price; 3] {
+        [
+            Price::eur(155., "motor-mall"),
+            Price::eur(115., "motor-mall"),
+            Price::eur(39., "Sorotec"),
+        ]
+    }
 
-    pub fn new(power: Power) -> Self {
-        Self { power }
+    /// Calculate the speed the spindle actually runs at, given a desired speed
+    pub fn speed(&self, rotational_speed: RotationalSpeed) -> RotationalSpeed {
+        rotational_speed.clamp(self.min_speed, self.max_speed)
     }
 
     /// Calculate spindle torque in Nm at a given speed in rpm
     pub fn torque(&self, rotational_speed: RotationalSpeed) -> Torque {
-        let rotational_speed = rotational_speed.clamp(Self::MIN, Self::MAX);
-        self.power.to_torque(rotational_speed)
+        self.power.to_torque(self.speed(rotational_speed))
+    }
+}
+
+/// The geometry of the spindle
+///
+/// This mirrors `cad/spindle.scad`, which has the dimensions from the
+/// drawing. A test checks that the two agree:
+/// https://www.zhonghuajiangspindle.com/1.5kw-cnc-air-cooled-spindle-motor-80mm.html
+pub struct SpindleGeometry {
+    /// The cylindrical segments that make up the spindle, from the nose up
+    pub segments: Vec<Segment>,
+
+    pub mass: Mass,
+}
+
+impl SpindleGeometry {
+    pub fn new() -> Self {
+        macro_rules! segments {
+            ($(
+                $name:expr, $height:expr, $diameter:expr, $color:expr,
+                $clamped:expr;
+            )*) => {
+                vec![
+                    $(
+                        Segment {
+                            name: $name,
+                            height: Length::from_value_mm($height),
+                            diameter: Diameter::from_length(
+                                Length::from_value_mm($diameter),
+                            ),
+                            color: $color,
+                            clamped: $clamped,
+                        },
+                    )*
+                ]
+            };
+        }
+
+        // The neck and connector are derived from the other heights in
+        // `spindle.scad`. Their diameters are guesses, as the drawing doesn't
+        // say. The clamp goes around the middle of the main body.
+        let segments = segments!(
+            "collet", 13., 19., Segment::BLACK, false;
+            "neck", 20., 15., Segment::SILVER, false;
+            "neck base", 3., 29.5, Segment::SILVER, false;
+            "shoulder", 18., 54., Segment::BLACK, false;
+            "body: bottom", 8., 80., Segment::BLACK, false;
+            "body: main", 164., 80., Segment::SILVER, true;
+            "body: top", 26., 80., Segment::BLACK, false;
+            "connector", 9., 20., Segment::SILVER, false;
+        );
+
+        Self {
+            segments,
+            // The sellers on eBay list the 1.5 kW air-cooled spindle at around
+            // 4.5 kg.
+            mass: Mass::from_value_kg(4.5),
+        }
+    }
+
+    /// The diameter of the collet nut, at the spindle nose
+    pub fn collet_nut(&self) -> Diameter {
+        self.segments[0].diameter
+    }
+
+    /// The total height of the spindle
+    pub fn height_total(&self) -> Length {
+        Length::from_value_mm(
+            self.segments
+                .iter()
+                .map(|segment| segment.height.value_mm())
+                .sum(),
+        )
+    }
+
+    /// The height of the center of gravity above the spindle nose
+    ///
+    /// Assumes uniform density, which isn't quite true (the motor windings are
+    /// denser than the air in the fan), but should be close enough.
+    pub fn center_of_gravity(&self) -> Length {
+        let mut bottom = 0.;
+        let mut volume = 0.;
+        let mut moment = 0.;
+
+        for segment in &self.segments {
+            let h = segment.height.value_mm();
+            let r = segment.diameter.to_radius().to_length().value_mm();
+            let v = PI * r.powi(2) * h;
+
+            volume += v;
+            moment += v * (bottom + h / 2.);
+            bottom += h;
+        }
+
+        Length::from_value_mm(moment / volume)
+    }
+
+    /// The height of the spindle clamp's center above the spindle nose
+    ///
+    /// The clamp goes around the middle of the clamped segments.
+    pub fn clamp_center(&self) -> Length {
+        let mut bottom = 0.;
+        let mut clamped = [f64::INFINITY, f64::NEG_INFINITY];
+        for segment in &self.segments {
+            let top = bottom + segment.height.value_mm();
+            if segment.clamped {
+                clamped = [clamped[0].min(bottom), clamped[1].max(top)];
+            }
+            bottom = top;
+        }
+
+        Length::from_value_mm((clamped[0] + clamped[1]) / 2.)
+    }
+}
+
+/// A cylindrical segment of the spindle
+pub struct Segment {
+    pub name: &'static str,
+    pub height: Length,
+    pub diameter: Diameter,
+    pub color: [u8; 4],
+
+    /// Whether the spindle clamp goes around this segment
+    pub clamped: bool,
+}
+
+impl Segment {
+    // The same colors as in `spindle.scad`.
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    const SILVER: [u8; 4] = [204, 204, 204, 255];
+}
+
+/// The spindle clamp, as modeled in `cad/Spindle Holder.stl`
+pub struct SpindleClamp {
+    pub mesh: Mesh,
+
+    /// The mass properties, in aluminium
+    pub properties: MassProperties,
+}
+
+impl SpindleClamp {
+    pub fn load() -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let cad = concat!(env!("CARGO_MANIFEST_DIR"), "/../cad");
+        let mesh = Mesh::load(format!("{cad}/Spindle Holder.stl"))?;
+        let properties = mesh.mass_properties(2700.);
+
+        Ok(Self { mesh, properties })
+    }
+
+    /// The width of the clamp, along x
+    pub fn width(&self) -> Length {
+        let [min, max] = self.mesh.bounding_box();
+        Length::from_value_mm(max[0].value_mm() - min[0].value_mm())
+    }
+
+    pub fn mass(&self) -> Mass {
+        self.properties.mass
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs, path::PathBuf};
+
+    use super::SpindleGeometry;
+
+    #[test]
+    fn segments_match_spindle_scad() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../cad/spindle.scad");
+        let scad = fs::read_to_string(path).unwrap();
+        let elements = elements(&scad);
+
+        let segments = SpindleGeometry::new().segments;
+        assert_eq!(segments.len(), elements.len());
+        for (segment, (name, [height, diameter], color)) in
+            segments.iter().zip(elements)
+        {
+            assert_eq!(segment.name, name);
+            assert_eq!(segment.height.value_mm(), height, "{name}");
+            assert_eq!(
+                segment.diameter.to_length().value_mm(),
+                diameter,
+                "{name}"
+            );
+            assert_eq!(segment.color, color, "{name}");
+        }
+    }
+
+    #[test]
+    fn clamp_is_around_the_main_body() {
+        let spindle = SpindleGeometry::new();
+        assert!(spindle.segments.iter().any(|segment| segment.clamped));
+
+        // Collet, neck, neck base, shoulder, and the bottom of the body are
+        // 62 mm. The main body is 164 mm.
+        assert_eq!(spindle.clamp_center().value_mm(), 62. + 164. / 2.);
+    }
+
+    /// Evaluate the `elements` table of `spindle.scad`
+    ///
+    /// That's only as much OpenSCAD as the file uses: variables, sums and
+    /// differences with spaces around the operators, and vectors of numbers.
+    /// The name of each element is in a comment after it.
+    fn elements(scad: &str) -> Vec<(String, [f64; 2], [u8; 4])> {
+        let (definitions, table) = scad.split_once("elements = [").unwrap();
+        let (table, _) = table.split_once("];").unwrap();
+
+        let definitions = definitions
+            .lines()
+            .map(|line| line.split("//").next().unwrap())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut variables = HashMap::new();
+        for statement in definitions.split(';') {
+            let Some((name, expression)) = statement.split_once('=') else {
+                continue;
+            };
+            let value = evaluate(expression, &variables);
+            variables.insert(name.trim().to_string(), value);
+        }
+
+        table
+            .lines()
+            .filter_map(|line| {
+                let (row, name) = line.split_once("//")?;
+                let row = row.trim().trim_end_matches(',');
+                let row = row.strip_prefix('[')?.strip_suffix(']')?;
+                let values = row
+                    .split(',')
+                    .map(|value| evaluate(value, &variables))
+                    .collect::<Vec<_>>();
+
+                let color =
+                    [0, 1, 2, 3].map(|i| (values[2][i] * 255.).round() as u8);
+                Some((
+                    name.trim().to_string(),
+                    [values[0][0], values[1][0]],
+                    color,
+                ))
+            })
+            .collect()
+    }
+
+    fn evaluate(
+        expression: &str,
+        variables: &HashMap<String, Vec<f64>>,
+    ) -> Vec<f64> {
+        let expression = expression.trim();
+        if let Some(vector) = expression
+            .strip_prefix('[')
+            .and_then(|vector| vector.strip_suffix(']'))
+        {
+            return vector
+                .split(',')
+                .map(|value| value.trim().parse().unwrap())
+                .collect();
+        }
+
+        if let Some(value) = variables.get(expression) {
+            return value.clone();
+        }
+
+        let operand =
+            |token: &str| token.parse().unwrap_or_else(|_| variables[token][0]);
+        let mut tokens = expression.split_whitespace();
+        let mut value = operand(tokens.next().unwrap());
+        while let Some(operator) = tokens.next() {
+            let rhs = operand(tokens.next().unwrap());
+            match operator {
+                "+" => value += rhs,
+                "-" => value -= rhs,
+                _ => panic!("unsupported operator `{operator}`"),
+            }
+        }
+        vec![value]
     }
 }
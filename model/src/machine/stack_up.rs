@@ -0,0 +1,150 @@
+use crate::{
+    machine::{axes::z, layout::Layout},
+    physics::Length,
+    tools::Tool,
+};
+
+/// The vertical stack-up between table and spindle
+///
+/// This is meant to answer the TASK in `cad/cnc-mill.scad`: What is a good
+/// value for the minimum spindle height? All heights are measured from the
+/// table surface to the bottom of the spindle's collet nut (the spindle nose),
+/// as that's where the tool sticks out of.
+pub struct StackUp {
+    /// The height of the vise (or whatever fixture holds the stock)
+    pub vise_height: Length,
+
+    /// The distance to keep between tool tip and stock, when moving above it
+    pub clearance: Length,
+
+    /// The height of the spindle nose, with the z-axis at its highest
+    pub nose_max: Length,
+
+    /// The height of the spindle nose, with the z-axis at its lowest
+    ///
+    /// That's where the z-axis runs out of stroke, or where the nose would
+    /// hit the table, whichever comes first.
+    pub nose_min: Length,
+}
+
+impl StackUp {
+    pub fn new(layout: &Layout) -> Self {
+        let nose_max = layout.outer[2].value_mm()
+            - layout.spindle_height.value_mm()
+            - layout.table_top().value_mm();
+        // The z-axis rails are only as long as that stroke, with the
+        // carriages on them. See `Layout::rail_length`.
+        let nose_min = (nose_max - z::max_travel().value_mm()).max(0.);
+
+        Self {
+            // A small machinist's vise, like the one assumed for the y-axis
+            // moving mass.
+            vise_height: Length::from_value_mm(40.),
+            clearance: Length::from_value_mm(5.),
+            nose_max: Length::from_value_mm(nose_max),
+            nose_min: Length::from_value_mm(nose_min),
+        }
+    }
+
+    /// Indicate whether the tool can reach the table surface
+    ///
+    /// That's what's required for the machine to surface its own table.
+    pub fn reaches_table(&self, tool: &Tool) -> bool {
+        tool.stick_out().value_mm() >= self.nose_min.value_mm()
+    }
+
+    /// Indicate whether the tool fits above stock of the given height
+    ///
+    /// The stock is held in the vise.
+    pub fn fits_above(&self, tool: &Tool, stock_height: Length) -> bool {
+        let required = self.vise_height.value_mm()
+            + stock_height.value_mm()
+            + tool.stick_out().value_mm()
+            + self.clearance.value_mm();
+
+        required <= self.nose_max.value_mm()
+    }
+
+    /// The range the spindle nose must be able to cover
+    ///
+    /// The lowest position is where the shortest tool can reach the table,
+    /// the highest is where the longest tool clears the highest stock. The
+    /// difference between them is the required z travel.
+    pub fn required_nose_range(
+        &self,
+        tools: &[Tool],
+        max_stock_height: Length,
+    ) -> [Length; 2] {
+        let stick_outs = tools.iter().map(|tool| tool.stick_out().value_mm());
+        let shortest = stick_outs.clone().fold(f64::INFINITY, f64::min);
+        let longest = stick_outs.fold(0., f64::max);
+
+        let highest = self.vise_height.value_mm()
+            + max_stock_height.value_mm()
+            + longest
+            + self.clearance.value_mm();
+
+        [
+            Length::from_value_mm(shortest),
+            Length::from_value_mm(highest),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        machine::{
+            configuration::Configuration, layout::Layout, rails::Rail,
+            spindle::SpindleClamp,
+        },
+        physics::{Diameter, Length},
+        tools::Tool,
+    };
+
+    use super::StackUp;
+
+    #[test]
+    fn nose_goes_down_to_the_table_if_the_stroke_allows() {
+        let stack_up = StackUp::new(&layout(400.));
+        assert!(stack_up.nose_max.value_mm() < 100.);
+        assert_eq!(stack_up.nose_min.value_mm(), 0.);
+
+        assert!(stack_up.reaches_table(&tool(50.)));
+    }
+
+    #[test]
+    fn short_tool_does_not_reach_the_table_past_the_stroke() {
+        let stack_up = StackUp::new(&layout(500.));
+        let stroke =
+            stack_up.nose_max.value_mm() - stack_up.nose_min.value_mm();
+        assert!((stroke - 100.).abs() < 1e-9);
+
+        // That's 32 mm and 102 mm sticking out of the collet.
+        assert!(!stack_up.reaches_table(&tool(50.)));
+        assert!(stack_up.reaches_table(&tool(120.)));
+    }
+
+    /// The default layout, with the given height
+    fn layout(height_mm: f64) -> Layout {
+        let rail = Rail::rails()
+            .into_iter()
+            .find(|rail| rail.name == "MGN15H")
+            .unwrap();
+        let spindle_clamp = SpindleClamp::load().unwrap();
+        let mut layout =
+            Layout::new(Configuration::FixedGantry, rail, &spindle_clamp);
+        layout.outer[2] = Length::from_value_mm(height_mm);
+        layout
+    }
+
+    /// A 6 mm end mill with the given total length
+    fn tool(total_mm: f64) -> Tool {
+        Tool {
+            diameter: Diameter::from_length(Length::from_value_mm(6.)),
+            length_cutting_edge: Length::from_value_mm(15.),
+            length_total: Length::from_value_mm(total_mm),
+            num_flutes: 2.,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Export of the machine geometry to OpenSCAD
+//!
+//! The CAD in `cad/` is handwritten. This generates a file next to it, from
+//! the same parts that the Fornjot model shows, so the two can't diverge.
+
+use std::fmt::Write;
+
+use crate::geometry::{Part, Primitive};
+
+/// Generate an OpenSCAD file from the machine's parts
+///
+/// Each group of parts becomes a module, and all of them are placed at the
+/// top level.
+pub fn export(parts: &[Part]) -> String {
+    let mut groups: Vec<&str> = Vec::new();
+    for part in parts {
+        if !groups.contains(&part.group) {
+            groups.push(part.group);
+        }
+    }
+
+    let mut scad = String::new();
+    writeln!(scad, "// Generated by the model in `model/`. Don't edit!")
+        .unwrap();
+    writeln!(scad).unwrap();
+    writeln!(scad, "$fn = 60;").unwrap();
+    writeln!(scad).unwrap();
+    for group in &groups {
+        writeln!(scad, "{group}();").unwrap();
+    }
+
+    for group in groups {
+        writeln!(scad).unwrap();
+        writeln!(scad, "module {group}() {{").unwrap();
+        for part in parts.iter().filter(|part| part.group == group) {
+            let [r, g, b, a] = part.color.map(|c| f64::from(c) / 255.);
+            writeln!(scad, "    // {}", part.name).unwrap();
+            writeln!(scad, "    color([{r:.2}, {g:.2}, {b:.2}, {a:.2}])")
+                .unwrap();
+
+            match part.primitive {
+                Primitive::Block { center, size, bore } => {
+                    writeln!(scad, "    translate({})", vector(center))
+                        .unwrap();
+                    match bore {
+                        Some(diameter) => {
+                            writeln!(scad, "    difference() {{").unwrap();
+                            writeln!(
+                                scad,
+                                "        cube({}, center = true);",
+                                vector(size),
+                            )
+                            .unwrap();
+                            writeln!(
+                                scad,
+                                "        cylinder(d = {diameter}, h = {}, \
+                                center = true);",
+                                size[2] + 1.,
+                            )
+                            .unwrap();
+                            writeln!(scad, "    }}").unwrap();
+                        }
+                        None => {
+                            writeln!(
+                                scad,
+                                "    cube({}, center = true);",
+                                vector(size),
+                            )
+                            .unwrap();
+                        }
+                    }
+                }
+                Primitive::Cylinder {
+                    base,
+                    diameter,
+                    height,
+                } => {
+                    writeln!(scad, "    translate({})", vector(base)).unwrap();
+                    writeln!(
+                        scad,
+                        "    cylinder(d = {diameter}, h = {height});"
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        writeln!(scad, "}}").unwrap();
+    }
+
+    scad
+}
+
+fn vector([x, y, z]: [f64; 3]) -> String {
+    format!("[{x:.2}, {y:.2}, {z:.2}]")
+}
@@ -0,0 +1,390 @@
+//! Search for the best combination of catalogue parts
+//!
+//! Picking rails, screws, and motors by hand means checking every combination
+//! against every calculation, which gets old fast. This goes through all of
+//! them, drops the ones that violate a constraint, and keeps those that aren't
+//! beaten on cost, stiffness, and feed by any other.
+//!
+//! To keep the search space manageable, all axes use the same rail, screw,
+//! and motor, which is what the README plans anyway. Driver and power supply
+//! follow from the motor: the cheapest ones that work with it.
+//!
+//! The spindle is left out. There's only the one that's configured, and the
+//! rest of the design is sized around it.
+
+use std::fmt;
+
+use crate::{
+    bom::Bom,
+    budget::Budget,
+    compliance::ComplianceChain,
+    frame::FixedGantryFrame,
+    machine::{
+        axes::Axis,
+        configuration::Kinematics,
+        drivers::StepperDriver,
+        drives::{self, AxisDrive},
+        electronics::Electronics,
+        layout::Layout,
+        limits::AxisLimits,
+        motors::StepperMotor,
+        power_supply::PowerSupply,
+        rails::Rail,
+        screws::BallScrew,
+        spindle::{Spindle, SpindleGeometry},
+    },
+    physics::{Force, Length, Speed, Stiffness},
+    tools::Tool,
+};
+
+/// The constraints that every design must satisfy
+pub struct Constraints {
+    /// The minimum static safety factor of the rail carriages
+    pub rail_safety_factor: f64,
+
+    /// The minimum rated life of the ball screws
+    pub screw_life: Length,
+
+    /// The feed at which the motors must still have their torque margin
+    pub min_feed: Speed,
+
+    /// The minimum travel along x, y, and z
+    pub min_travel: [Length; 3],
+}
+
+impl Constraints {
+    pub fn new() -> Self {
+        Self {
+            // HIWIN recommends 3 or more for loads with vibration, which
+            // cutting forces definitely are.
+            rail_safety_factor: 3.,
+            // That's with the worst-case cutting force acting all the time,
+            // which is very conservative. A modest target is enough.
+            screw_life: Length::from_value_m(1_000_000.),
+            min_feed: Speed::from_value_mm_per_min(1000.),
+            // About what the current design gets. A machine with less than
+            // that is not worth building.
+            min_travel: [
+                Length::from_value_mm(150.),
+                Length::from_value_mm(150.),
+                Length::from_value_mm(80.),
+            ],
+        }
+    }
+}
+
+/// Everything that stays the same while searching the catalogues
+pub struct DesignSpace<'r> {
+    pub layout: &'r Layout,
+
+    /// The drives of the configured design
+    ///
+    /// Rail, screw, motor, and driver are replaced during the search, and
+    /// screw lengths and moving masses follow from them. The rest stays as it
+    /// is.
+    pub drives: &'r [AxisDrive],
+
+    pub frame: &'r FixedGantryFrame,
+    pub electronics: &'r Electronics,
+    pub spindle: &'r Spindle,
+    pub spindle_geometry: &'r SpindleGeometry,
+
+    /// The tool that the tool-tip stiffness is computed for
+    pub tool: &'r Tool,
+
+    pub cutting_force: Force,
+    pub budget: f64,
+    pub constraints: Constraints,
+}
+
+impl DesignSpace<'_> {
+    /// Evaluate all combinations of catalogue parts
+    pub fn search(&self) -> Search {
+        let mut search = Search {
+            evaluated: 0,
+            rejected: Vec::new(),
+            pareto_front: Vec::new(),
+        };
+
+        let mut feasible = Vec::new();
+        for rail in Rail::rails() {
+            for screw in BallScrew::screws() {
+                for motor in StepperMotor::motors() {
+                    search.evaluated += 1;
+
+                    match self.evaluate(&rail, &screw, &motor) {
+                        Ok(candidate) => feasible.push(candidate),
+                        Err(violation) => {
+                            match search
+                                .rejected
+                                .iter_mut()
+                                .find(|(v, _)| *v == violation)
+                            {
+                                Some((_, count)) => *count += 1,
+                                None => search.rejected.push((violation, 1)),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        search.pareto_front = feasible
+            .iter()
+            .filter(|candidate| {
+                !feasible.iter().any(|other| other.dominates(candidate))
+            })
+            .cloned()
+            .collect();
+        search
+            .pareto_front
+            .sort_by(|a, b| a.cost.total_cmp(&b.cost));
+
+        search
+    }
+
+    fn evaluate(
+        &self,
+        rail: &Rail,
+        screw: &BallScrew,
+        motor: &StepperMotor,
+    ) -> Result<Candidate, Violation> {
+        let layout = Layout {
+            rail: rail.clone(),
+            ..self.layout.clone()
+        };
+        for (axis, min_travel) in [Axis::X, Axis::Y, Axis::Z]
+            .into_iter()
+            .zip(self.constraints.min_travel)
+        {
+            if layout.envelope(axis).travel() < min_travel {
+                return Err(Violation::Envelope);
+            }
+        }
+
+        // All axes use the same motor, and some carry the motors of others.
+        let kinematics = Kinematics::new(&layout, self.frame, [motor.mass; 3]);
+
+        let (drives, electronics) = self
+            .drives(&layout, &kinematics, screw, motor)
+            .ok_or(Violation::Electronics)?;
+
+        for axis in &kinematics.axes {
+            let load = rail.carriage_load(
+                self.cutting_force,
+                axis.lever_arm,
+                axis.carriage_spacing,
+            );
+            if rail.static_safety_factor(load)
+                < self.constraints.rail_safety_factor
+            {
+                return Err(Violation::RailSafetyFactor);
+            }
+        }
+
+        let acceleration = drives::design_acceleration();
+        for drive in &drives {
+            let axial_force =
+                drive.axial_force(self.cutting_force, acceleration);
+            if drive.screw.rated_life(axial_force) < self.constraints.screw_life
+            {
+                return Err(Violation::ScrewLife);
+            }
+
+            let margin = drive.torque_margin(
+                self.constraints.min_feed,
+                self.cutting_force,
+                acceleration,
+                electronics.power_supply.voltage,
+            );
+            if margin < drive.safety_factor {
+                return Err(Violation::MotorTorque);
+            }
+        }
+
+        let bom = Bom::new(
+            &layout,
+            &drives,
+            self.spindle,
+            self.spindle_geometry,
+            &electronics,
+        );
+        let cost = Budget::new(&bom, self.budget).total();
+
+        let stiffness = [Axis::X, Axis::Y]
+            .map(|direction| {
+                ComplianceChain::new(
+                    direction, self.tool, rail, self.frame, &drives,
+                )
+                .total_stiffness()
+            })
+            .into_iter()
+            .reduce(|a, b| if a < b { a } else { b })
+            .unwrap();
+
+        let max_feed = drives
+            .iter()
+            .map(|drive| {
+                AxisLimits::new(
+                    drive,
+                    self.cutting_force,
+                    electronics.power_supply.voltage,
+                )
+                .max_feed_velocity
+                .speed
+            })
+            .reduce(|a, b| if a < b { a } else { b })
+            .unwrap();
+
+        Ok(Candidate {
+            rail: rail.name,
+            screw: screw.name,
+            motor: motor.name,
+            driver: drives[0].driver.name,
+            power_supply: electronics.power_supply.name,
+            cost,
+            stiffness,
+            max_feed,
+        })
+    }
+
+    /// Build the drives, with the cheapest driver that works with the motor
+    ///
+    /// The screws need to cover the travel of the layout. Nut, supports, and
+    /// coupling take up as much length as they do in the configured design.
+    fn drives(
+        &self,
+        layout: &Layout,
+        kinematics: &Kinematics,
+        screw: &BallScrew,
+        motor: &StepperMotor,
+    ) -> Option<(Vec<AxisDrive>, Electronics)> {
+        let screw_lengths = self
+            .drives
+            .iter()
+            .map(|drive| {
+                let travel = |layout: &Layout| {
+                    layout.envelope(drive.axis).travel().value_mm()
+                };
+                Length::from_value_mm(
+                    drive.screw_length.value_mm() + travel(layout)
+                        - travel(self.layout),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut drivers = StepperDriver::drivers();
+        drivers.sort_by(|a, b| {
+            a.price.value_eur().total_cmp(&b.price.value_eur())
+        });
+
+        let voltage = self.electronics.power_supply.voltage;
+
+        drivers.into_iter().find_map(|driver| {
+            let drives = self
+                .drives
+                .iter()
+                .zip(&screw_lengths)
+                .map(|(drive, &screw_length)| AxisDrive {
+                    axis: drive.axis,
+                    driver: StepperDriver::by_name(driver.name),
+                    microsteps: drive.microsteps,
+                    motor: StepperMotor::by_name(motor.name),
+                    screw: BallScrew::by_name(screw.name),
+                    screw_length,
+                    moving_mass: kinematics.axis(drive.axis).moving_mass,
+                    safety_factor: drive.safety_factor,
+                })
+                .collect::<Vec<_>>();
+
+            let mut electronics = Electronics {
+                power_supply: PowerSupply::by_name(
+                    self.electronics.power_supply.name,
+                ),
+                controller_power: self.electronics.controller_power,
+            };
+            let required_power = electronics.required_power(&drives);
+            electronics.power_supply =
+                PowerSupply::recommend(voltage, required_power)?;
+
+            electronics
+                .check(&drives)
+                .is_empty()
+                .then_some((drives, electronics))
+        })
+    }
+}
+
+/// The result of [`DesignSpace::search`]
+pub struct Search {
+    /// The number of combinations that were evaluated
+    pub evaluated: usize,
+
+    /// The number of combinations rejected, per violated constraint
+    pub rejected: Vec<(Violation, usize)>,
+
+    /// The feasible designs that no other design beats in every respect
+    ///
+    /// Sorted by cost.
+    pub pareto_front: Vec<Candidate>,
+}
+
+/// A feasible design
+#[derive(Clone)]
+pub struct Candidate {
+    pub rail: &'static str,
+    pub screw: &'static str,
+    pub motor: &'static str,
+    pub driver: &'static str,
+    pub power_supply: &'static str,
+
+    /// The total cost, in €
+    pub cost: f64,
+
+    /// The tool-tip stiffness, in the weaker of x and y
+    pub stiffness: Stiffness,
+
+    /// The max feed velocity of the slowest axis
+    pub max_feed: Speed,
+}
+
+impl Candidate {
+    /// Indicate whether this candidate is at least as good in all respects,
+    /// and better in at least one
+    pub fn dominates(&self, other: &Self) -> bool {
+        let at_least_as_good = self.cost <= other.cost
+            && self.stiffness >= other.stiffness
+            && self.max_feed >= other.max_feed;
+        let better = self.cost < other.cost
+            || self.stiffness > other.stiffness
+            || self.max_feed > other.max_feed;
+
+        at_least_as_good && better
+    }
+}
+
+/// The constraint that rejected a design
+///
+/// Only the first one found is reported.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Violation {
+    Envelope,
+    Electronics,
+    RailSafetyFactor,
+    ScrewLife,
+    MotorTorque,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::Envelope => write!(f, "not enough travel"),
+            Violation::Electronics => write!(f, "no suitable driver"),
+            Violation::RailSafetyFactor => {
+                write!(f, "rail safety factor too low")
+            }
+            Violation::ScrewLife => write!(f, "ball screw life too short"),
+            Violation::MotorTorque => write!(f, "motor torque margin too low"),
+        }
+    }
+}
@@ -1,9 +1,92 @@
+// The conversions take `&self`, like all other methods here, even though the
+// types are `Copy`.
+#![allow(clippy::wrong_self_convention)]
+
 use std::{
     f64::consts::{PI, TAU},
     fmt,
-    ops::{Add, Div},
+    ops::{Add, Div, Mul, Sub},
 };
 
+/// The standard acceleration of gravity
+pub const STANDARD_GRAVITY: Acceleration =
+    Acceleration::from_value_m_per_s2(9.81);
+
+/// An acceleration
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Acceleration(f64);
+
+impl Acceleration {
+    /// Create an instance of `Acceleration` from a value in m/s²
+    pub const fn from_value_m_per_s2(acceleration_m_per_s2: f64) -> Self {
+        Self(acceleration_m_per_s2)
+    }
+
+    /// Return the value in m/s²
+    pub fn value_m_per_s2(&self) -> f64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Acceleration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.2} m/s²", self.value_m_per_s2())
+    }
+}
+
+/// An area
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Area(f64);
+
+impl Area {
+    /// Create an instance of `Area` from a value in m²
+    pub const fn from_value_m2(area_m2: f64) -> Self {
+        Self(area_m2)
+    }
+
+    /// Create an instance of `Area` from a value in cm²
+    ///
+    /// That's the unit that extrusion catalogues usually use.
+    pub fn from_value_cm2(area_cm2: f64) -> Self {
+        Self::from_value_m2(area_cm2 / 10_000.)
+    }
+
+    /// Return the value in m²
+    pub fn value_m2(&self) -> f64 {
+        self.0
+    }
+}
+
+impl Mul<f64> for Area {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self(self.0 * rhs)
+    }
+}
+
+/// An electric current
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Current(f64);
+
+impl Current {
+    /// Create an instance of `Current` from a value in Ampere
+    pub const fn from_value_a(current_a: f64) -> Self {
+        Self(current_a)
+    }
+
+    /// Return the value in Ampere
+    pub fn value_a(&self) -> f64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Current {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.2} A", self.value_a())
+    }
+}
+
 /// A diameter
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct Diameter(Length);
@@ -47,12 +130,109 @@ impl Force {
     }
 }
 
+impl Add<Self> for Force {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Mul<f64> for Force {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self(self.0 * rhs)
+    }
+}
+
 impl fmt::Display for Force {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:.2} N", self.value_n())
     }
 }
 
+/// A frequency
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Frequency(f64);
+
+impl Frequency {
+    /// Create an instance of `Frequency` from a value in Hertz
+    pub const fn from_value_hz(frequency_hz: f64) -> Self {
+        Self(frequency_hz)
+    }
+
+    /// Create an instance of `Frequency` from a value in kilohertz
+    pub fn from_value_khz(frequency_khz: f64) -> Self {
+        Self::from_value_hz(frequency_khz * 1000.)
+    }
+
+    /// Return the value in Hertz
+    pub fn value_hz(&self) -> f64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1} Hz", self.value_hz())
+    }
+}
+
+/// An inductance
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Inductance(f64);
+
+impl Inductance {
+    /// Create an instance of `Inductance` from a value in Henry
+    pub const fn from_value_h(inductance_h: f64) -> Self {
+        Self(inductance_h)
+    }
+
+    /// Create an instance of `Inductance` from a value in millihenry
+    pub fn from_value_mh(inductance_mh: f64) -> Self {
+        Self::from_value_h(inductance_mh / 1000.)
+    }
+
+    /// Return the value in Henry
+    pub fn value_h(&self) -> f64 {
+        self.0
+    }
+
+    /// Return the value in millihenry
+    pub fn value_mh(&self) -> f64 {
+        self.0 * 1000.
+    }
+}
+
+impl fmt::Display for Inductance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.2} mH", self.value_mh())
+    }
+}
+
+/// A jerk, the rate of change of acceleration
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Jerk(f64);
+
+impl Jerk {
+    /// Create an instance of `Jerk` from a value in m/s³
+    pub const fn from_value_m_per_s3(jerk_m_per_s3: f64) -> Self {
+        Self(jerk_m_per_s3)
+    }
+
+    /// Return the value in m/s³
+    pub fn value_m_per_s3(&self) -> f64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Jerk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1} m/s³", self.value_m_per_s3())
+    }
+}
+
 /// A length
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct Length(f64);
@@ -87,6 +267,14 @@ impl Add<Self> for Length {
     }
 }
 
+impl Sub<Self> for Length {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
 impl Div<f64> for Length {
     type Output = Self;
 
@@ -95,6 +283,94 @@ impl Div<f64> for Length {
     }
 }
 
+/// A mass
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Mass(f64);
+
+impl Mass {
+    /// Create an instance of `Mass` from a value in kilogram
+    pub const fn from_value_kg(mass_kg: f64) -> Self {
+        Self(mass_kg)
+    }
+
+    /// Return the value in kilogram
+    pub fn value_kg(&self) -> f64 {
+        self.0
+    }
+}
+
+impl Add<Self> for Mass {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Mul<f64> for Mass {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self(self.0 * rhs)
+    }
+}
+
+impl Mul<Acceleration> for Mass {
+    type Output = Force;
+
+    fn mul(self, rhs: Acceleration) -> Self::Output {
+        Force::from_value_n(self.value_kg() * rhs.value_m_per_s2())
+    }
+}
+
+impl fmt::Display for Mass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.2} kg", self.value_kg())
+    }
+}
+
+/// A mass moment of inertia
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct MomentOfInertia(f64);
+
+impl MomentOfInertia {
+    /// Create an instance of `MomentOfInertia` from a value in kg·m²
+    pub const fn from_value_kg_m2(moment_of_inertia_kg_m2: f64) -> Self {
+        Self(moment_of_inertia_kg_m2)
+    }
+
+    /// Create an instance of `MomentOfInertia` from a value in g·cm²
+    ///
+    /// That's the unit that stepper motor datasheets usually use.
+    pub fn from_value_g_cm2(moment_of_inertia_g_cm2: f64) -> Self {
+        Self::from_value_kg_m2(moment_of_inertia_g_cm2 / 10_000_000.)
+    }
+
+    /// Return the value in kg·m²
+    pub fn value_kg_m2(&self) -> f64 {
+        self.0
+    }
+
+    /// Return the value in g·cm²
+    pub fn value_g_cm2(&self) -> f64 {
+        self.0 * 10_000_000.
+    }
+}
+
+impl Add<Self> for MomentOfInertia {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl fmt::Display for MomentOfInertia {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.0} g·cm²", self.value_g_cm2())
+    }
+}
+
 /// A power value
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct Power(f64);
@@ -119,6 +395,60 @@ impl Power {
     }
 }
 
+impl Add<Self> for Power {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Mul<f64> for Power {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self(self.0 * rhs)
+    }
+}
+
+impl fmt::Display for Power {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.0} W", self.value_w())
+    }
+}
+
+/// A pressure
+///
+/// Also used for anything else that is measured in Pascal, like stresses or
+/// elastic moduli.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Pressure(f64);
+
+impl Pressure {
+    /// Create an instance of `Pressure` from a value in Pascal
+    pub const fn from_value_pa(pressure_pa: f64) -> Self {
+        Self(pressure_pa)
+    }
+
+    /// Create an instance of `Pressure` from a value in Gigapascal
+    pub fn from_value_gpa(pressure_gpa: f64) -> Self {
+        Self::from_value_pa(pressure_gpa * 1_000_000_000.)
+    }
+
+    /// Return the value in Pascal
+    pub fn value_pa(&self) -> f64 {
+        self.0
+    }
+}
+
+impl Mul<f64> for Pressure {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self(self.0 * rhs)
+    }
+}
+
 /// A radius
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct Radius(Length);
@@ -149,6 +479,28 @@ impl Add<Self> for Radius {
     }
 }
 
+/// An electrical resistance
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Resistance(f64);
+
+impl Resistance {
+    /// Create an instance of `Resistance` from a value in Ohm
+    pub const fn from_value_ohm(resistance_ohm: f64) -> Self {
+        Self(resistance_ohm)
+    }
+
+    /// Return the value in Ohm
+    pub fn value_ohm(&self) -> f64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Resistance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.2} Ω", self.value_ohm())
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct RotationalSpeed(f64);
 
@@ -170,6 +522,53 @@ impl RotationalSpeed {
     }
 }
 
+impl fmt::Display for RotationalSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.0} rpm", self.value_rpm())
+    }
+}
+
+/// A rotational stiffness
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct RotationalStiffness(f64);
+
+impl RotationalStiffness {
+    /// Create an instance of `RotationalStiffness` from a value in Nm/rad
+    pub const fn from_value_nm_per_rad(stiffness_nm_per_rad: f64) -> Self {
+        Self(stiffness_nm_per_rad)
+    }
+
+    /// Return the value in Nm/rad
+    pub fn value_nm_per_rad(&self) -> f64 {
+        self.0
+    }
+}
+
+/// A second moment of area
+///
+/// Also used for the torsion constant, which has the same unit.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct SecondMomentOfArea(f64);
+
+impl SecondMomentOfArea {
+    /// Create an instance of `SecondMomentOfArea` from a value in m⁴
+    pub const fn from_value_m4(second_moment_of_area_m4: f64) -> Self {
+        Self(second_moment_of_area_m4)
+    }
+
+    /// Create an instance of `SecondMomentOfArea` from a value in cm⁴
+    ///
+    /// That's the unit that extrusion catalogues usually use.
+    pub fn from_value_cm4(second_moment_of_area_cm4: f64) -> Self {
+        Self::from_value_m4(second_moment_of_area_cm4 / 100_000_000.)
+    }
+
+    /// Return the value in m⁴
+    pub fn value_m4(&self) -> f64 {
+        self.0
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct Speed(f64);
 
@@ -182,10 +581,22 @@ impl Speed {
         Self::from_value_m_per_s(speed_m_per_min / 60.)
     }
 
+    pub fn from_value_mm_per_min(speed_mm_per_min: f64) -> Self {
+        Self::from_value_m_per_min(speed_mm_per_min / 1000.)
+    }
+
+    pub fn value_m_per_s(&self) -> f64 {
+        self.0
+    }
+
     pub fn value_m_per_min(&self) -> f64 {
         self.0 * 60.
     }
 
+    pub fn value_mm_per_min(&self) -> f64 {
+        self.value_m_per_min() * 1000.
+    }
+
     pub fn to_rotational_speed(
         &self,
         radius: impl Into<Radius>,
@@ -196,6 +607,54 @@ impl Speed {
     }
 }
 
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.0} mm/min", self.value_mm_per_min())
+    }
+}
+
+/// A (linear) stiffness
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Stiffness(f64);
+
+impl Stiffness {
+    /// Create an instance of `Stiffness` from a value in N/m
+    pub const fn from_value_n_per_m(stiffness_n_per_m: f64) -> Self {
+        Self(stiffness_n_per_m)
+    }
+
+    /// Create an instance of `Stiffness` from a compliance in m/N
+    pub fn from_compliance_m_per_n(compliance_m_per_n: f64) -> Self {
+        Self::from_value_n_per_m(1. / compliance_m_per_n)
+    }
+
+    /// Create an instance of `Stiffness` from a value in N/µm
+    pub fn from_value_n_per_um(stiffness_n_per_um: f64) -> Self {
+        Self::from_value_n_per_m(stiffness_n_per_um * 1_000_000.)
+    }
+
+    /// Return the value in N/m
+    pub fn value_n_per_m(&self) -> f64 {
+        self.0
+    }
+
+    /// Return the value in N/µm
+    pub fn value_n_per_um(&self) -> f64 {
+        self.0 / 1_000_000.
+    }
+
+    /// Compute the deflection under the given force
+    pub fn to_deflection(&self, force: Force) -> Length {
+        Length::from_value_m(force.value_n() / self.value_n_per_m())
+    }
+}
+
+impl fmt::Display for Stiffness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.2} N/µm", self.value_n_per_um())
+    }
+}
+
 /// A torque
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct Torque(f64);
@@ -218,8 +677,80 @@ impl Torque {
     }
 }
 
+impl Add<Self> for Torque {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
 impl fmt::Display for Torque {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:.2} Nm", self.value_nm())
     }
 }
+
+/// A voltage
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Voltage(f64);
+
+impl Voltage {
+    /// Create an instance of `Voltage` from a value in Volt
+    pub const fn from_value_v(voltage_v: f64) -> Self {
+        Self(voltage_v)
+    }
+
+    /// Return the value in Volt
+    pub fn value_v(&self) -> f64 {
+        self.0
+    }
+}
+
+impl Mul<Current> for Voltage {
+    type Output = Power;
+
+    fn mul(self, rhs: Current) -> Self::Output {
+        Power::from_value_w(self.value_v() * rhs.value_a())
+    }
+}
+
+impl fmt::Display for Voltage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1} V", self.value_v())
+    }
+}
+
+/// A volume
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Volume(f64);
+
+impl Volume {
+    /// Create an instance of `Volume` from a value in m³
+    pub const fn from_value_m3(volume_m3: f64) -> Self {
+        Self(volume_m3)
+    }
+
+    /// Create an instance of `Volume` from a value in mm³
+    ///
+    /// That's the unit that CAD files usually use.
+    pub fn from_value_mm3(volume_mm3: f64) -> Self {
+        Self::from_value_m3(volume_mm3 / 1_000_000_000.)
+    }
+
+    /// Return the value in m³
+    pub fn value_m3(&self) -> f64 {
+        self.0
+    }
+
+    /// Return the value in mm³
+    pub fn value_mm3(&self) -> f64 {
+        self.0 * 1_000_000_000.
+    }
+}
+
+impl fmt::Display for Volume {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1} cm³", self.value_m3() * 1_000_000.)
+    }
+}
@@ -0,0 +1,416 @@
+//! Reading STL files of purchased parts, and writing our own
+//!
+//! Vendors often provide STL files for their parts. Those don't say anything
+//! about mass, but given a density, we can compute the mass properties from the
+//! mesh. That only works for closed meshes, which vendor files usually are.
+//!
+//! STL files don't have units either. All the ones we have are in mm, and so
+//! are the ones we write.
+
+use std::{error::Error, fs, io, path::Path};
+
+use crate::physics::{Length, Mass, MomentOfInertia, Volume};
+
+/// A triangle mesh, as loaded from an STL file
+pub struct Mesh {
+    /// The triangles of the mesh, with their vertices in mm
+    pub triangles: Vec<[[f64; 3]; 3]>,
+}
+
+impl Mesh {
+    /// Load a mesh from an STL file, ASCII or binary
+    ///
+    /// Fails, if the file can't be read or parsed, or if the mesh doesn't
+    /// enclose a positive volume. That happens if it's not closed, or if its
+    /// triangles are wound the wrong way, and the mass properties would be
+    /// garbage.
+    pub fn load(
+        path: impl AsRef<Path>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = path.as_ref();
+        let data = fs::read(path)
+            .map_err(|err| format!("Can't read `{}`: {err}", path.display()))?;
+
+        // Binary files have an 80-byte header, followed by the number of
+        // triangles and 50 bytes per triangle. ASCII files start with "solid",
+        // but some binary files do too, so checking the size is more robust.
+        let is_binary = data.len() >= 84 && {
+            let num_triangles =
+                u32::from_le_bytes(data[80..84].try_into().unwrap()) as usize;
+            data.len() == 84 + num_triangles * 50
+        };
+
+        let triangles = if is_binary {
+            Ok(Self::parse_binary(&data))
+        } else {
+            String::from_utf8(data)
+                .map_err(|_| "neither binary STL, nor text".to_string())
+                .and_then(|text| Self::parse_ascii(&text))
+        }
+        .map_err(|err| format!("Can't parse `{}`: {err}", path.display()))?;
+
+        let mesh = Self { triangles };
+        let volume = mesh.mass_properties(1.).volume.value_mm3();
+        if volume.is_nan() || volume <= 0. {
+            return Err(format!(
+                "Can't use `{}`: its volume is {volume} mm³, so it's not \
+                closed, or inside out",
+                path.display()
+            )
+            .into());
+        }
+
+        Ok(mesh)
+    }
+
+    /// Save the mesh as a binary STL file
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut data = vec![0; 80];
+        data.extend((self.triangles.len() as u32).to_le_bytes());
+
+        for triangle in &self.triangles {
+            let [a, b, c] = *triangle;
+            let u = [0, 1, 2].map(|i| b[i] - a[i]);
+            let v = [0, 1, 2].map(|i| c[i] - a[i]);
+            let normal = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            let length = normal.iter().map(|n| n * n).sum::<f64>().sqrt();
+
+            for value in normal
+                .map(|n| if length > 0. { n / length } else { 0. })
+                .iter()
+                .chain(triangle.iter().flatten())
+            {
+                data.extend((*value as f32).to_le_bytes());
+            }
+            // The attribute byte count, which nobody uses.
+            data.extend([0; 2]);
+        }
+
+        fs::write(path, data)
+    }
+
+    fn parse_binary(data: &[u8]) -> Vec<[[f64; 3]; 3]> {
+        data[84..]
+            .chunks_exact(50)
+            .map(|chunk| {
+                // Each triangle is a normal, three vertices, and an attribute
+                // byte count. We compute the normal ourselves.
+                let float = |i: usize| {
+                    let offset = 12 + i * 4;
+                    f64::from(f32::from_le_bytes(
+                        chunk[offset..offset + 4].try_into().unwrap(),
+                    ))
+                };
+                let vertex = |i: usize| {
+                    [float(i * 3), float(i * 3 + 1), float(i * 3 + 2)]
+                };
+
+                [vertex(0), vertex(1), vertex(2)]
+            })
+            .collect()
+    }
+
+    fn parse_ascii(text: &str) -> Result<Vec<[[f64; 3]; 3]>, String> {
+        let vertices = text
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let coordinates = line.trim().strip_prefix("vertex")?;
+                Some((i + 1, coordinates))
+            })
+            .map(|(line, coordinates)| {
+                let coordinates = coordinates
+                    .split_whitespace()
+                    .map(str::parse::<f64>)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| format!("line {line}: {err}"))?;
+                match coordinates[..] {
+                    [x, y, z] => Ok([x, y, z]),
+                    _ => Err(format!(
+                        "line {line}: vertex with {} coordinates",
+                        coordinates.len()
+                    )),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if vertices.len() % 3 != 0 {
+            return Err(format!(
+                "{} vertices don't make up triangles",
+                vertices.len()
+            ));
+        }
+
+        Ok(vertices
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .collect())
+    }
+
+    /// The axis-aligned bounding box, as minimum and maximum corner
+    pub fn bounding_box(&self) -> [[Length; 3]; 2] {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+
+        for vertex in self.triangles.iter().flatten() {
+            for i in 0..3 {
+                min[i] = min[i].min(vertex[i]);
+                max[i] = max[i].max(vertex[i]);
+            }
+        }
+
+        [
+            min.map(Length::from_value_mm),
+            max.map(Length::from_value_mm),
+        ]
+    }
+
+    /// Compute the mass properties of the mesh, for the given density
+    ///
+    /// `density` is in kg/m³.
+    pub fn mass_properties(&self, density: f64) -> MassProperties {
+        // This is the algorithm from David Eberly's "Polyhedral Mass
+        // Properties (Revisited)". It sums up the volume integrals of 1, x, y,
+        // z, x², y², z², xy, yz, and zx over the tetrahedra formed by each
+        // triangle and the origin.
+        let mut integrals = [0.; 10];
+
+        for [p0, p1, p2] in &self.triangles {
+            let e1 = [p1[0] - p0[0], p1[1] - p0[1], p1[2] - p0[2]];
+            let e2 = [p2[0] - p0[0], p2[1] - p0[1], p2[2] - p0[2]];
+            let d = [
+                e1[1] * e2[2] - e2[1] * e1[2],
+                e2[0] * e1[2] - e1[0] * e2[2],
+                e1[0] * e2[1] - e2[0] * e1[1],
+            ];
+
+            let [x, y, z] =
+                [0, 1, 2].map(|i| Subexpressions::new(p0[i], p1[i], p2[i]));
+
+            integrals[0] += d[0] * x.f1;
+            integrals[1] += d[0] * x.f2;
+            integrals[2] += d[1] * y.f2;
+            integrals[3] += d[2] * z.f2;
+            integrals[4] += d[0] * x.f3;
+            integrals[5] += d[1] * y.f3;
+            integrals[6] += d[2] * z.f3;
+            integrals[7] +=
+                d[0] * (p0[1] * x.g[0] + p1[1] * x.g[1] + p2[1] * x.g[2]);
+            integrals[8] +=
+                d[1] * (p0[2] * y.g[0] + p1[2] * y.g[1] + p2[2] * y.g[2]);
+            integrals[9] +=
+                d[2] * (p0[0] * z.g[0] + p1[0] * z.g[1] + p2[0] * z.g[2]);
+        }
+
+        let factors = [
+            1. / 6.,
+            1. / 24.,
+            1. / 24.,
+            1. / 24.,
+            1. / 60.,
+            1. / 60.,
+            1. / 60.,
+            1. / 120.,
+            1. / 120.,
+            1. / 120.,
+        ];
+        for (integral, factor) in integrals.iter_mut().zip(factors) {
+            *integral *= factor;
+        }
+
+        // All of these are in mm. They're converted to SI units at the end.
+        let volume = integrals[0];
+        let c = [
+            integrals[1] / volume,
+            integrals[2] / volume,
+            integrals[3] / volume,
+        ];
+
+        // The second moments, relative to the centroid.
+        let xx = integrals[5] + integrals[6]
+            - volume * (c[1].powi(2) + c[2].powi(2));
+        let yy = integrals[4] + integrals[6]
+            - volume * (c[2].powi(2) + c[0].powi(2));
+        let zz = integrals[4] + integrals[5]
+            - volume * (c[0].powi(2) + c[1].powi(2));
+        let xy = -(integrals[7] - volume * c[0] * c[1]);
+        let yz = -(integrals[8] - volume * c[1] * c[2]);
+        let zx = -(integrals[9] - volume * c[2] * c[0]);
+
+        // mm⁵ to m⁵, times kg/m³.
+        let inertia = |value: f64| {
+            MomentOfInertia::from_value_kg_m2(value * 1e-15 * density)
+        };
+
+        let volume = Volume::from_value_mm3(volume);
+
+        MassProperties {
+            volume,
+            mass: Mass::from_value_kg(volume.value_m3() * density),
+            centroid: c.map(Length::from_value_mm),
+            inertia: [
+                [inertia(xx), inertia(xy), inertia(zx)],
+                [inertia(xy), inertia(yy), inertia(yz)],
+                [inertia(zx), inertia(yz), inertia(zz)],
+            ],
+        }
+    }
+}
+
+/// The mass properties of a [`Mesh`]
+pub struct MassProperties {
+    pub volume: Volume,
+    pub mass: Mass,
+    pub centroid: [Length; 3],
+
+    /// The inertia tensor, relative to the centroid
+    pub inertia: [[MomentOfInertia; 3]; 3],
+}
+
+/// Subexpressions of the integrals in [`Mesh::mass_properties`]
+struct Subexpressions {
+    f1: f64,
+    f2: f64,
+    f3: f64,
+    g: [f64; 3],
+}
+
+impl Subexpressions {
+    fn new(w0: f64, w1: f64, w2: f64) -> Self {
+        let temp0 = w0 + w1;
+        let f1 = temp0 + w2;
+        let temp1 = w0 * w0;
+        let temp2 = temp1 + w1 * temp0;
+        let f2 = temp2 + w2 * f1;
+        let f3 = w0 * temp1 + w1 * temp2 + w2 * f2;
+
+        Self {
+            f1,
+            f2,
+            f3,
+            g: [
+                f2 + w0 * (f1 + w0),
+                f2 + w1 * (f1 + w1),
+                f2 + w2 * (f1 + w2),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, process};
+
+    use super::Mesh;
+
+    #[test]
+    fn mass_properties_of_a_cube() {
+        // A density of 10⁹ kg/m³ makes the 1 mm³ cube weigh 1 kg.
+        let properties = cube([1., 2., 3.]).mass_properties(1e9);
+
+        assert!((properties.volume.value_mm3() - 1.).abs() < 1e-9);
+        assert!((properties.mass.value_kg() - 1.).abs() < 1e-9);
+        for (centroid, expected) in
+            properties.centroid.iter().zip([1.5, 2.5, 3.5])
+        {
+            assert!((centroid.value_mm() - expected).abs() < 1e-9);
+        }
+
+        // m (a² + a²) / 12 around each axis, and no products of inertia.
+        let expected = 1. * (2. * 1e-6) / 12.;
+        for (i, row) in properties.inertia.iter().enumerate() {
+            for (j, inertia) in row.iter().enumerate() {
+                let expected = if i == j { expected } else { 0. };
+                assert!(
+                    (inertia.value_kg_m2() - expected).abs() < 1e-12,
+                    "{i}, {j}: {}",
+                    inertia.value_kg_m2(),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = temp_path("cube.stl");
+        cube([0.; 3]).save(&path).unwrap();
+        let mesh = Mesh::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(mesh.triangles.len(), 12);
+        assert!(
+            (mesh.mass_properties(1.).volume.value_mm3() - 1.).abs() < 1e-6
+        );
+    }
+
+    #[test]
+    fn load_ascii() {
+        let mut text = String::from("solid cube\n");
+        for triangle in cube([0.; 3]).triangles {
+            text.push_str("facet normal 0 0 0\nouter loop\n");
+            for [x, y, z] in triangle {
+                text.push_str(&format!("vertex {x} {y} {z}\n"));
+            }
+            text.push_str("endloop\nendfacet\n");
+        }
+        text.push_str("endsolid cube\n");
+
+        let path = temp_path("cube-ascii.stl");
+        fs::write(&path, &text).unwrap();
+        let mesh = Mesh::load(&path);
+        fs::write(&path, text.replacen("vertex 0 0 0", "vertex 0 0", 1))
+            .unwrap();
+        let malformed = Mesh::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(mesh.unwrap().triangles.len(), 12);
+        assert!(malformed.is_err());
+    }
+
+    #[test]
+    fn reject_inside_out_meshes() {
+        let mut mesh = cube([0.; 3]);
+        for triangle in &mut mesh.triangles {
+            triangle.swap(1, 2);
+        }
+
+        let path = temp_path("inside-out.stl");
+        mesh.save(&path).unwrap();
+        let result = Mesh::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(result.is_err());
+        assert!(Mesh::load(temp_path("missing.stl")).is_err());
+    }
+
+    /// A 1 mm cube, with its lowest corner at `corner`
+    fn cube(corner: [f64; 3]) -> Mesh {
+        let [x, y, z] = corner;
+        let p = |dx: f64, dy: f64, dz: f64| [x + dx, y + dy, z + dz];
+
+        // The corners of each face go counter-clockwise, seen from outside.
+        let faces = [
+            [p(0., 0., 0.), p(0., 1., 0.), p(1., 1., 0.), p(1., 0., 0.)],
+            [p(0., 0., 1.), p(1., 0., 1.), p(1., 1., 1.), p(0., 1., 1.)],
+            [p(0., 0., 0.), p(1., 0., 0.), p(1., 0., 1.), p(0., 0., 1.)],
+            [p(0., 1., 0.), p(0., 1., 1.), p(1., 1., 1.), p(1., 1., 0.)],
+            [p(0., 0., 0.), p(0., 0., 1.), p(0., 1., 1.), p(0., 1., 0.)],
+            [p(1., 0., 0.), p(1., 1., 0.), p(1., 1., 1.), p(1., 0., 1.)],
+        ];
+
+        Mesh {
+            triangles: faces
+                .iter()
+                .flat_map(|[a, b, c, d]| [[*a, *b, *c], [*a, *c, *d]])
+                .collect(),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("model-{}-{name}", process::id()))
+    }
+}
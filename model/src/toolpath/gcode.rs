@@ -0,0 +1,545 @@
+//! A parser for the subset of G-code that GRBL understands, and CAM emits
+//!
+//! This covers motion (G0-G3), dwells (G4), planes (G17-G19), units
+//! (G20/G21), distance modes (G90/G91), feed, spindle, and tool changes.
+//!
+//! CAM puts more than that into the preamble of every program: work
+//! coordinate systems, coolant, and modes that are GRBL's defaults anyway.
+//! Those don't change where the tool goes, so they're accepted, and ignored.
+//! Anything else is an error, rather than silently ignored, so a program the
+//! model doesn't understand can't produce misleading results.
+//!
+//! Like GRBL, the parser starts out in G0, G17, G21, and G90, at the origin.
+
+use std::{fmt, time::Duration};
+
+use crate::physics::{Length, RotationalSpeed, Speed};
+
+use super::{Motion, Plane, Point, Segment};
+
+/// Parse a program into the segments of its toolpath
+pub fn parse(program: &str) -> Result<Vec<Segment>, ParseError> {
+    let mut state = State {
+        position: [0.; 3],
+        motion: MotionMode::Rapid,
+        plane: Plane::Xy,
+        inches: false,
+        incremental: false,
+        feed: None,
+        spindle_speed: 0.,
+        spindle_on: false,
+        selected_tool: None,
+        tool: None,
+    };
+    let mut segments = Vec::new();
+
+    for (i, text) in program.lines().enumerate() {
+        let line = i + 1;
+        let error = |message: String| ParseError { line, message };
+
+        let words = words(text).map_err(error)?;
+        let (segment, end) = state.execute(&words, line).map_err(error)?;
+        segments.extend(segment);
+        if end {
+            break;
+        }
+    }
+
+    Ok(segments)
+}
+
+/// An error in a G-code program
+#[derive(Debug)]
+pub struct ParseError {
+    /// The line of the program, starting at 1
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The modal state of the controller
+struct State {
+    /// The current position, in mm
+    position: [f64; 3],
+
+    motion: MotionMode,
+    plane: Plane,
+    inches: bool,
+    incremental: bool,
+
+    /// The feed rate, in mm/min
+    feed: Option<f64>,
+
+    /// The spindle speed, in rpm
+    spindle_speed: f64,
+    spindle_on: bool,
+
+    /// The tool selected by T, which M6 puts into the spindle
+    selected_tool: Option<u32>,
+    tool: Option<u32>,
+}
+
+impl State {
+    /// Execute a line, returning the move it makes, and whether the program
+    /// ends there
+    fn execute(
+        &mut self,
+        words: &[(char, f64)],
+        line: usize,
+    ) -> Result<(Option<Segment>, bool), String> {
+        let mut axes = [None; 3];
+        let mut offsets = [None; 3];
+        let mut radius = None;
+        let mut feed = None;
+        let mut dwell = None;
+        let mut motion = None;
+        let mut is_dwell = false;
+        let mut tool_change = false;
+        let mut end = false;
+
+        // First pass: Collect the words. Modal settings that affect how the
+        // others are interpreted (units, distance mode) take effect before
+        // anything moves, as in GRBL.
+        for &(letter, value) in words {
+            let set = |slot: &mut Option<f64>| {
+                if slot.is_some() {
+                    return Err(format!("`{letter}` appears more than once"));
+                }
+                *slot = Some(value);
+                Ok(())
+            };
+
+            match letter {
+                // Incremental arc offsets, which is the only kind we support.
+                'G' if value == 91.1 => {}
+                'G' => match code(value)? {
+                    0 => motion = Some(MotionMode::Rapid),
+                    1 => motion = Some(MotionMode::Linear),
+                    2 => motion = Some(MotionMode::Arc { clockwise: true }),
+                    3 => motion = Some(MotionMode::Arc { clockwise: false }),
+                    4 => is_dwell = true,
+                    17 => self.plane = Plane::Xy,
+                    18 => self.plane = Plane::Zx,
+                    19 => self.plane = Plane::Yz,
+                    20 => self.inches = true,
+                    21 => self.inches = false,
+                    90 => self.incremental = false,
+                    91 => self.incremental = true,
+                    // Cutter radius and tool length compensation off, work
+                    // coordinate systems, exact path mode, canned cycles off,
+                    // and feed per minute. The program is in work coordinates
+                    // anyway, and the rest is what GRBL does.
+                    40 | 49 | 54..=59 | 61 | 80 | 94 => {}
+                    _ => return Err(format!("unsupported command `G{value}`")),
+                },
+                'M' => match code(value)? {
+                    // Pauses, which don't change the toolpath.
+                    0 | 1 => {}
+                    2 | 30 => end = true,
+                    // The direction doesn't matter for the loads.
+                    3 | 4 => self.spindle_on = true,
+                    5 => self.spindle_on = false,
+                    6 => tool_change = true,
+                    // Coolant.
+                    7..=9 => {}
+                    _ => return Err(format!("unsupported command `M{value}`")),
+                },
+                'X' => set(&mut axes[0])?,
+                'Y' => set(&mut axes[1])?,
+                'Z' => set(&mut axes[2])?,
+                'I' => set(&mut offsets[0])?,
+                'J' => set(&mut offsets[1])?,
+                'K' => set(&mut offsets[2])?,
+                'R' => set(&mut radius)?,
+                // In GRBL, the dwell time is in seconds.
+                'P' => set(&mut dwell)?,
+                'F' => {
+                    if value <= 0. {
+                        return Err("feed rate must be positive".to_string());
+                    }
+                    set(&mut feed)?;
+                }
+                'S' => {
+                    if value < 0. {
+                        return Err(
+                            "spindle speed must not be negative".to_string()
+                        );
+                    }
+                    self.spindle_speed = value;
+                }
+                'T' => self.selected_tool = Some(code(value)?),
+                // Line numbers don't mean anything to us.
+                'N' => {}
+                _ => return Err(format!("unsupported word `{letter}`")),
+            }
+        }
+
+        let units = if self.inches { 25.4 } else { 1. };
+        if let Some(feed) = feed {
+            self.feed = Some(feed * units);
+        }
+
+        if tool_change {
+            self.tool = self.selected_tool;
+        }
+        if let Some(motion) = motion {
+            self.motion = motion;
+        }
+
+        let has_axes = axes.iter().any(Option::is_some);
+        let has_arc_words =
+            offsets.iter().any(Option::is_some) || radius.is_some();
+
+        if is_dwell {
+            if has_axes || has_arc_words {
+                return Err("dwell with a move on the same line".to_string());
+            }
+            let seconds = match dwell {
+                Some(seconds) if seconds >= 0. && seconds.is_finite() => {
+                    seconds
+                }
+                Some(_) => {
+                    return Err("dwell time must not be negative".to_string())
+                }
+                None => return Err("dwell without `P`".to_string()),
+            };
+
+            let segment = Segment {
+                line,
+                start: to_point(self.position),
+                end: to_point(self.position),
+                motion: Motion::Dwell(Duration::from_secs_f64(seconds)),
+                feed: Speed::from_value_mm_per_min(self.feed.unwrap_or(0.)),
+                spindle_speed: self.spindle_speed(),
+                tool: self.tool,
+            };
+            return Ok((Some(segment), end));
+        }
+        if dwell.is_some() {
+            return Err("`P` without dwell".to_string());
+        }
+        if !has_axes {
+            if motion.is_some() && matches!(self.motion, MotionMode::Arc { .. })
+            {
+                return Err("arc without end point".to_string());
+            }
+            if has_arc_words {
+                return Err("arc words without a move".to_string());
+            }
+            return Ok((None, end));
+        }
+
+        let start = self.position;
+        let mut target = start;
+        for (i, value) in axes.into_iter().enumerate() {
+            if let Some(value) = value {
+                target[i] = if self.incremental {
+                    start[i] + value * units
+                } else {
+                    value * units
+                };
+            }
+        }
+
+        let feed = match (self.motion, self.feed) {
+            (MotionMode::Rapid, feed) => feed.unwrap_or(0.),
+            (_, Some(feed)) => feed,
+            (_, None) => return Err("feed move without feed rate".to_string()),
+        };
+
+        let motion = match self.motion {
+            MotionMode::Rapid | MotionMode::Linear => {
+                if has_arc_words {
+                    return Err("arc words in a straight move".to_string());
+                }
+                if self.motion == MotionMode::Rapid {
+                    Motion::Rapid
+                } else {
+                    Motion::Linear
+                }
+            }
+            MotionMode::Arc { clockwise } => {
+                let center = self.arc_center(
+                    start,
+                    target,
+                    offsets.map(|offset| offset.map(|o| o * units)),
+                    radius.map(|r| r * units),
+                    clockwise,
+                )?;
+
+                Motion::Arc {
+                    center: to_point(center),
+                    plane: self.plane,
+                    clockwise,
+                }
+            }
+        };
+
+        self.position = target;
+
+        let segment = Segment {
+            line,
+            start: to_point(start),
+            end: to_point(target),
+            motion,
+            feed: Speed::from_value_mm_per_min(feed),
+            spindle_speed: self.spindle_speed(),
+            tool: self.tool,
+        };
+
+        // GRBL still executes the move, before ending the program.
+        Ok((Some(segment), end))
+    }
+
+    /// The programmed spindle speed, if the spindle is running
+    fn spindle_speed(&self) -> Option<RotationalSpeed> {
+        self.spindle_on
+            .then(|| RotationalSpeed::from_value_rpm(self.spindle_speed))
+    }
+
+    /// Compute the center of an arc, in mm
+    fn arc_center(
+        &self,
+        start: [f64; 3],
+        target: [f64; 3],
+        offsets: [Option<f64>; 3],
+        radius: Option<f64>,
+        clockwise: bool,
+    ) -> Result<[f64; 3], String> {
+        let [a, b, linear] = self.plane.axes();
+        let mut center = start;
+
+        match radius {
+            Some(r) => {
+                if offsets.iter().any(Option::is_some) {
+                    return Err("arc with both radius and offsets".to_string());
+                }
+
+                // See `mc_arc` and `gc_execute_line` in GRBL. There are two
+                // arcs with the given radius. A negative radius selects the
+                // longer one.
+                let x = target[a] - start[a];
+                let y = target[b] - start[b];
+                if x == 0. && y == 0. {
+                    return Err(
+                        "full circles can't be specified by radius".to_string()
+                    );
+                }
+
+                let h_x2_div_d = 4. * r * r - x * x - y * y;
+                if h_x2_div_d < 0. {
+                    return Err(format!(
+                        "arc radius {} mm is too small for its end point",
+                        r.abs()
+                    ));
+                }
+                let mut h = -h_x2_div_d.sqrt() / x.hypot(y);
+                if !clockwise {
+                    h = -h;
+                }
+                if r < 0. {
+                    h = -h;
+                }
+
+                center[a] += 0.5 * (x - y * h);
+                center[b] += 0.5 * (y + x * h);
+            }
+            None => {
+                if offsets[linear].is_some() {
+                    return Err(
+                        "arc offset along the axis normal to its plane"
+                            .to_string(),
+                    );
+                }
+                if offsets[a].is_none() && offsets[b].is_none() {
+                    return Err("arc without radius or offsets".to_string());
+                }
+
+                center[a] += offsets[a].unwrap_or(0.);
+                center[b] += offsets[b].unwrap_or(0.);
+
+                // Same tolerance as GRBL.
+                let r_start =
+                    (start[a] - center[a]).hypot(start[b] - center[b]);
+                let r_end =
+                    (target[a] - center[a]).hypot(target[b] - center[b]);
+                let deviation = (r_end - r_start).abs();
+                if deviation > 0.005 && deviation > 0.001 * r_start {
+                    return Err(format!(
+                        "arc end point is {deviation:.3} mm off the arc"
+                    ));
+                }
+            }
+        }
+
+        Ok(center)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MotionMode {
+    Rapid,
+    Linear,
+    Arc { clockwise: bool },
+}
+
+/// Split a line into words, after removing comments
+fn words(line: &str) -> Result<Vec<(char, f64)>, String> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            // A comment in parentheses, which doesn't nest.
+            '(' => {
+                if !chars.by_ref().any(|c| c == ')') {
+                    return Err("unclosed comment".to_string());
+                }
+            }
+            // A comment to the end of the line.
+            ';' => break,
+            // Marks the start and end of a program, when sent from a file.
+            '%' => {}
+            c if c.is_whitespace() => {}
+            c if c.is_ascii_alphabetic() => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || matches!(c, '.' | '-' | '+') {
+                        number.push(c);
+                        chars.next();
+                    } else if c == ' ' && number.is_empty() {
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                let letter = c.to_ascii_uppercase();
+                let value = number
+                    .parse()
+                    .map_err(|_| format!("invalid number after `{letter}`"))?;
+                words.push((letter, value));
+            }
+            c => return Err(format!("unexpected character `{c}`")),
+        }
+    }
+
+    Ok(words)
+}
+
+/// Interpret the value of a G, M, or T word as an integer code
+fn code(value: f64) -> Result<u32, String> {
+    if value < 0. || value.fract() != 0. {
+        return Err(format!("unsupported code `{value}`"));
+    }
+    Ok(value as u32)
+}
+
+fn to_point(position: [f64; 3]) -> Point {
+    position.map(Length::from_value_mm)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::PI, time::Duration};
+
+    use crate::toolpath::{Motion, Point, Segment};
+
+    use super::parse;
+
+    #[test]
+    fn arc_by_radius_takes_the_shorter_or_longer_way() {
+        let segments =
+            parse("G3 X10 Y10 R10 F100\nG0 X0 Y0\nG3 X10 Y10 R-10").unwrap();
+
+        // Counter-clockwise from the origin, the short way around.
+        assert_close(center(&segments[0]), [0., 10., 0.]);
+        assert_close(mm(segments[0].end), [10., 10., 0.]);
+        assert!((segments[0].sweep() - PI / 2.).abs() < 1e-9);
+
+        // A negative radius goes the long way around the other center.
+        assert_close(center(&segments[2]), [10., 0., 0.]);
+        assert!((segments[2].sweep() - 3. * PI / 2.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn arc_by_offsets() {
+        let segments = parse("G2 X10 Y0 I5 J0 F100").unwrap();
+        let arc = &segments[0];
+
+        assert_close(center(arc), [5., 0., 0.]);
+        assert!((arc.sweep() + PI).abs() < 1e-9);
+        assert!((arc.length().value_mm() - 5. * PI).abs() < 1e-9);
+
+        // Clockwise from the left, over the top.
+        assert_close(mm(arc.point_at(0.5)), [5., 5., 0.]);
+    }
+
+    #[test]
+    fn arc_end_point_must_be_on_the_arc() {
+        let error = parse("G21\nG2 X10 Y0 I4 J0 F100").unwrap_err();
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn inches_and_incremental_distances() {
+        let segments = parse("G20 G91\nG1 X1 F10\nG1 X1 Y-0.5").unwrap();
+
+        assert_close(mm(segments[0].end), [25.4, 0., 0.]);
+        assert_close(mm(segments[1].end), [50.8, -12.7, 0.]);
+        assert!((segments[1].feed.value_mm_per_min() - 254.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn preamble_codes_are_ignored() {
+        let program = "G90 G94 G91.1 G40 G49 G17\nG54\nM8\nS1000 M4\nG4 P1.5\n\
+            G1 X1 F100\nM9 M5\nM30";
+        let segments = parse(program).unwrap();
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!(
+            segments[0].motion,
+            Motion::Dwell(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            segments[1].spindle_speed.map(|speed| speed.value_rpm()),
+            Some(1000.)
+        );
+    }
+
+    #[test]
+    fn codes_that_move_the_tool_are_errors() {
+        for program in ["G28 G91 Z0", "G53 G0 Z0", "G43.1 Z5", "G92 X0"] {
+            assert!(parse(program).is_err(), "{program}");
+        }
+        assert!(parse("G4").is_err());
+        assert!(parse("G1 X1 F100 P2").is_err());
+    }
+
+    fn center(segment: &Segment) -> [f64; 3] {
+        match segment.motion {
+            Motion::Arc { center, .. } => mm(center),
+            motion => panic!("not an arc: {motion:?}"),
+        }
+    }
+
+    fn mm(point: Point) -> [f64; 3] {
+        point.map(|coordinate| coordinate.value_mm())
+    }
+
+    fn assert_close(actual: [f64; 3], expected: [f64; 3]) {
+        for i in 0..3 {
+            assert!(
+                (actual[i] - expected[i]).abs() < 1e-9,
+                "{actual:?} != {expected:?}"
+            );
+        }
+    }
+}
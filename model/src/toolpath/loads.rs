@@ -0,0 +1,370 @@
+//! The loads that a program puts on the machine
+//!
+//! The design is sized for a worst-case cutting force. A program might stay
+//! well below that, or it might not, if CAM was set up for a bigger machine.
+//! Replaying it through the stock tells how deep and wide each move cuts, and
+//! from that, what it asks of the spindle, the rails, and the motors.
+
+use std::fmt;
+
+use crate::{
+    machine::{
+        axes::Axis, configuration::Kinematics, drives::AxisDrive, rails::Rail,
+        spindle::Spindle,
+    },
+    physics::{
+        Acceleration, Force, Length, RotationalSpeed, Speed, Torque, Voltage,
+    },
+    tools::Tool,
+};
+
+use super::{
+    stock::{Cut, Engagement, Stock},
+    Motion, Segment, Toolpath,
+};
+
+/// The parts of the machine that cutting loads
+pub struct CuttingLoads<'r> {
+    pub tools: &'r [Tool],
+    pub spindle: &'r Spindle,
+    pub drives: &'r [AxisDrive],
+    pub rail: &'r Rail,
+    pub kinematics: &'r Kinematics,
+    pub supply_voltage: Voltage,
+    pub rail_safety_factor: f64,
+}
+
+impl CuttingLoads<'_> {
+    /// Replay a program through the stock
+    ///
+    /// Returns the moves that cut something, and those that would, if the
+    /// program made sense.
+    pub fn replay(
+        &self,
+        toolpath: &Toolpath,
+        stock: &mut Stock,
+    ) -> Vec<MoveLoad> {
+        let mut loads = Vec::new();
+
+        for segment in &toolpath.segments {
+            let Some(tool) = segment
+                .tool
+                .and_then(|number| super::tool(number, self.tools))
+            else {
+                // Without the tool, there's no telling what the move cuts.
+                // Below the top of the stock, it's probably something.
+                let below_top =
+                    segment.start[2].value_mm().min(segment.end[2].value_mm())
+                        < 0.;
+                if segment.is_feed() && below_top {
+                    loads.push(MoveLoad {
+                        line: segment.line,
+                        cut: None,
+                        forces: None,
+                        overloads: vec![Overload::UnknownTool(segment.tool)],
+                    });
+                }
+                continue;
+            };
+
+            let cut = stock.cut(segment, tool);
+            if !cut.is_cutting()
+                && cut.shank_collision.is_none()
+                && cut.collet_collision.is_none()
+            {
+                continue;
+            }
+
+            let mut overloads = Vec::new();
+            if let Some(depth) = cut.shank_collision {
+                overloads.push(Overload::ShankCollision { depth });
+            }
+            if let Some(depth) = cut.collet_collision {
+                overloads.push(Overload::ColletCollision { depth });
+            }
+            let forces = match (segment.motion, segment.spindle_speed) {
+                (Motion::Rapid, _) => {
+                    if cut.is_cutting() {
+                        overloads.push(Overload::RapidIntoStock);
+                    }
+                    None
+                }
+                (_, None) => {
+                    overloads.push(Overload::SpindleOff);
+                    None
+                }
+                (_, Some(spindle_speed)) => {
+                    let forces = self.forces(
+                        segment,
+                        tool,
+                        cut.engagement,
+                        spindle_speed,
+                    );
+                    overloads.extend(self.overloads(&forces));
+                    Some(forces)
+                }
+            };
+
+            loads.push(MoveLoad {
+                line: segment.line,
+                cut: Some(cut),
+                forces,
+                overloads,
+            });
+        }
+
+        loads
+    }
+
+    fn forces(
+        &self,
+        segment: &Segment,
+        tool: &Tool,
+        engagement: Engagement,
+        spindle_speed: RotationalSpeed,
+    ) -> Forces {
+        let spindle_speed = self.spindle.speed(spindle_speed);
+        let feed_per_tooth = Length::from_value_m(
+            segment.feed.value_mm_per_min()
+                / 1000.
+                / (spindle_speed.value_rpm() * tool.num_flutes),
+        );
+        let cutting_force = tool.cutting_force(
+            engagement.axial,
+            engagement.radial,
+            feed_per_tooth,
+        );
+
+        // Like for sizing the drives, let's assume the cutting force can act
+        // along any of the axes. The feed is constant while cutting, so it's
+        // not accelerating.
+        let acceleration = Acceleration::from_value_m_per_s2(0.);
+        let shares = axis_shares(segment);
+        let axes = self
+            .drives
+            .iter()
+            .zip(shares)
+            .map(|(drive, share)| {
+                let speed = Speed::from_value_mm_per_min(
+                    segment.feed.value_mm_per_min() * share,
+                );
+                let kinematics = self.kinematics.axis(drive.axis);
+                let carriage_load = self.rail.carriage_load(
+                    cutting_force,
+                    kinematics.lever_arm,
+                    kinematics.carriage_spacing,
+                );
+
+                AxisLoad {
+                    axis: drive.axis,
+                    speed,
+                    torque_margin: drive.torque_margin(
+                        speed,
+                        cutting_force,
+                        acceleration,
+                        self.supply_voltage,
+                    ),
+                    rail_safety_factor: self
+                        .rail
+                        .static_safety_factor(carriage_load),
+                }
+            })
+            .collect();
+
+        Forces {
+            feed_per_tooth,
+            cutting_force,
+            spindle_torque: cutting_force.to_torque(tool.diameter),
+            available_spindle_torque: self.spindle.torque(spindle_speed),
+            axes,
+        }
+    }
+
+    fn overloads(&self, forces: &Forces) -> Vec<Overload> {
+        let mut overloads = Vec::new();
+
+        if forces.spindle_torque > forces.available_spindle_torque {
+            overloads.push(Overload::SpindleTorque {
+                required: forces.spindle_torque,
+                available: forces.available_spindle_torque,
+            });
+        }
+        for (load, drive) in forces.axes.iter().zip(self.drives) {
+            if load.torque_margin < drive.safety_factor {
+                overloads.push(Overload::MotorTorque {
+                    axis: load.axis,
+                    margin: load.torque_margin,
+                    required: drive.safety_factor,
+                });
+            }
+        }
+        for load in &forces.axes {
+            if load.rail_safety_factor < self.rail_safety_factor {
+                overloads.push(Overload::RailSafetyFactor {
+                    axis: load.axis,
+                    factor: load.rail_safety_factor,
+                    required: self.rail_safety_factor,
+                });
+            }
+        }
+
+        overloads
+    }
+}
+
+/// What a move cuts, and what that asks of the machine
+pub struct MoveLoad {
+    /// The line of the program that the move comes from
+    pub line: usize,
+
+    /// What the move does to the stock
+    ///
+    /// `None`, if the tool isn't known.
+    pub cut: Option<Cut>,
+
+    /// The forces of the cut
+    ///
+    /// `None`, if the move shouldn't be cutting at all.
+    pub forces: Option<Forces>,
+
+    /// Anything that the machine can't take
+    pub overloads: Vec<Overload>,
+}
+
+pub struct Forces {
+    /// The feed per tooth, as programmed
+    pub feed_per_tooth: Length,
+
+    /// The tangential cutting force
+    pub cutting_force: Force,
+
+    /// The torque that the cut requires from the spindle
+    pub spindle_torque: Torque,
+
+    /// The torque that the spindle delivers at the programmed speed
+    pub available_spindle_torque: Torque,
+
+    /// The loads on the axes, in the order x, y, z
+    pub axes: Vec<AxisLoad>,
+}
+
+pub struct AxisLoad {
+    pub axis: Axis,
+
+    /// How fast the axis moves during the cut
+    pub speed: Speed,
+
+    /// The ratio of available to required motor torque
+    pub torque_margin: f64,
+
+    /// The static safety factor of the most highly loaded carriage
+    pub rail_safety_factor: f64,
+}
+
+/// A way in which a move is too much for the machine
+pub enum Overload {
+    /// The move cuts with a tool that's not in the catalogue, if any
+    UnknownTool(Option<u32>),
+
+    /// A rapid move cuts into the stock
+    RapidIntoStock,
+
+    /// A move cuts while the spindle is off
+    SpindleOff,
+
+    /// Material reaches past the cutting edge, to the shank
+    ShankCollision { depth: Length },
+
+    /// Material reaches past the tool, to the collet nut
+    ColletCollision { depth: Length },
+
+    /// The cut needs more torque than the spindle has
+    SpindleTorque { required: Torque, available: Torque },
+
+    /// A motor has less torque margin than its safety factor
+    MotorTorque {
+        axis: Axis,
+        margin: f64,
+        required: f64,
+    },
+
+    /// A rail carriage is loaded beyond its static safety factor
+    RailSafetyFactor {
+        axis: Axis,
+        factor: f64,
+        required: f64,
+    },
+}
+
+impl fmt::Display for Overload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Overload::UnknownTool(Some(number)) => {
+                write!(
+                    f,
+                    "cutting with T{number}, which isn't in the catalogue"
+                )
+            }
+            Overload::UnknownTool(None) => {
+                write!(f, "cutting without a tool in the spindle")
+            }
+            Overload::RapidIntoStock => write!(f, "rapid move into the stock"),
+            Overload::SpindleOff => write!(f, "cutting with the spindle off"),
+            Overload::ShankCollision { depth } => write!(
+                f,
+                "shank goes {:.2} mm into the material",
+                depth.value_mm()
+            ),
+            Overload::ColletCollision { depth } => write!(
+                f,
+                "collet nut goes {:.2} mm into the material",
+                depth.value_mm()
+            ),
+            Overload::SpindleTorque {
+                required,
+                available,
+            } => write!(
+                f,
+                "needs {required} of spindle torque, {available} available"
+            ),
+            Overload::MotorTorque {
+                axis,
+                margin,
+                required,
+            } => write!(
+                f,
+                "{axis}-axis motor torque margin {margin:.2}, needs {required}"
+            ),
+            Overload::RailSafetyFactor {
+                axis,
+                factor,
+                required,
+            } => write!(
+                f,
+                "{axis}-axis rail safety factor {factor:.1}, needs {required}"
+            ),
+        }
+    }
+}
+
+/// How much of the feed each axis moves at, at most
+fn axis_shares(segment: &Segment) -> [f64; 3] {
+    let length = segment.length().value_mm();
+    if length == 0. {
+        return [0.; 3];
+    }
+
+    let mut shares = [0, 1, 2].map(|i| {
+        (segment.end[i].value_mm() - segment.start[i].value_mm()).abs() / length
+    });
+
+    // Somewhere along an arc, each of the axes in its plane is likely to move
+    // at close to the full feed.
+    if let Motion::Arc { plane, .. } = segment.motion {
+        let [a, b, _] = plane.axes();
+        shares[a] = 1.;
+        shares[b] = 1.;
+    }
+
+    shares
+}
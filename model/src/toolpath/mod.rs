@@ -0,0 +1,267 @@
+//! Toolpaths, as read from G-code programs
+//!
+//! The machine is going to be driven by a GRBL-style controller, with CAM
+//! output sent by Candle, UGS, or ioSender. The calculations so far look at
+//! worst cases. With the actual programs, we can look at what the machine is
+//! really asked to do.
+
+pub mod gcode;
+pub mod loads;
+pub mod planner;
+pub mod stock;
+
+use std::{f64::consts::TAU, fmt, fs, io, path::PathBuf, time::Duration};
+
+use crate::{
+    physics::{Length, RotationalSpeed, Speed},
+    tools::Tool,
+};
+
+/// A point in machine coordinates, along x, y, and z
+pub type Point = [Length; 3];
+
+/// The moves of a program
+pub struct Toolpath {
+    pub segments: Vec<Segment>,
+}
+
+impl Toolpath {
+    /// Load and parse a G-code program
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+
+        let program = match fs::read_to_string(&path) {
+            Ok(program) => program,
+            Err(source) => return Err(Error::Read { path, source }),
+        };
+        match gcode::parse(&program) {
+            Ok(segments) => Ok(Self { segments }),
+            Err(source) => Err(Error::Parse { path, source }),
+        }
+    }
+
+    /// The tools used by the program, in the order they're first used
+    pub fn tools(&self) -> Vec<u32> {
+        let mut tools = Vec::new();
+        for tool in self.segments.iter().filter_map(|segment| segment.tool) {
+            if !tools.contains(&tool) {
+                tools.push(tool);
+            }
+        }
+        tools
+    }
+}
+
+/// Look up a tool by its number in a program
+///
+/// Tool numbers are indices into the tool catalogue, starting at 1.
+pub fn tool(number: u32, tools: &[Tool]) -> Option<&Tool> {
+    number
+        .checked_sub(1)
+        .and_then(|index| tools.get(index as usize))
+}
+
+/// A single move, or a dwell
+#[derive(Clone, Debug)]
+pub struct Segment {
+    /// The line of the program that the move comes from, starting at 1
+    pub line: usize,
+
+    pub start: Point,
+    pub end: Point,
+    pub motion: Motion,
+
+    /// The programmed feed rate
+    ///
+    /// Rapids move as fast as the machine can, so this doesn't apply to them.
+    pub feed: Speed,
+
+    /// The programmed spindle speed, if the spindle is running
+    pub spindle_speed: Option<RotationalSpeed>,
+
+    /// The number of the tool in the spindle, if any
+    pub tool: Option<u32>,
+}
+
+impl Segment {
+    /// Indicate whether the segment moves at the programmed feed
+    pub fn is_feed(&self) -> bool {
+        matches!(self.motion, Motion::Linear | Motion::Arc { .. })
+    }
+
+    /// The length of the path
+    pub fn length(&self) -> Length {
+        match self.motion {
+            Motion::Rapid | Motion::Linear | Motion::Dwell(_) => {
+                let [dx, dy, dz] = [0, 1, 2]
+                    .map(|i| self.end[i].value_m() - self.start[i].value_m());
+                Length::from_value_m((dx * dx + dy * dy + dz * dz).sqrt())
+            }
+            Motion::Arc { plane, .. } => {
+                let [_, _, linear] = plane.axes();
+                let arc = self.radius().value_m() * self.sweep().abs();
+                let helix =
+                    self.end[linear].value_m() - self.start[linear].value_m();
+                Length::from_value_m((arc * arc + helix * helix).sqrt())
+            }
+        }
+    }
+
+    /// The radius of an arc
+    ///
+    /// Zero, for straight moves.
+    pub fn radius(&self) -> Length {
+        match self.motion {
+            Motion::Rapid | Motion::Linear | Motion::Dwell(_) => {
+                Length::from_value_m(0.)
+            }
+            Motion::Arc { center, plane, .. } => {
+                let [a, b, _] = plane.axes();
+                let da = self.start[a].value_m() - center[a].value_m();
+                let db = self.start[b].value_m() - center[b].value_m();
+                Length::from_value_m(da.hypot(db))
+            }
+        }
+    }
+
+    /// The angle that an arc sweeps, in radians
+    ///
+    /// Positive for counter-clockwise arcs, negative for clockwise ones. Zero,
+    /// for straight moves.
+    pub fn sweep(&self) -> f64 {
+        let Motion::Arc {
+            center,
+            plane,
+            clockwise,
+        } = self.motion
+        else {
+            return 0.;
+        };
+
+        let [a, b, _] = plane.axes();
+        let angle = |point: &Point| {
+            (point[b].value_m() - center[b].value_m())
+                .atan2(point[a].value_m() - center[a].value_m())
+        };
+
+        let mut sweep = angle(&self.end) - angle(&self.start);
+        if clockwise {
+            if sweep >= -f64::EPSILON {
+                sweep -= TAU;
+            }
+        } else if sweep <= f64::EPSILON {
+            sweep += TAU;
+        }
+        sweep
+    }
+
+    /// The point at a fraction of the way along the path, from 0 to 1
+    pub fn point_at(&self, t: f64) -> Point {
+        let mut point = [0, 1, 2].map(|i| {
+            let start = self.start[i].value_m();
+            start + (self.end[i].value_m() - start) * t
+        });
+
+        if let Motion::Arc { center, plane, .. } = self.motion {
+            let [a, b, _] = plane.axes();
+            let [center_a, center_b] =
+                [center[a].value_m(), center[b].value_m()];
+            let start = (self.start[b].value_m() - center_b)
+                .atan2(self.start[a].value_m() - center_a);
+            let angle = start + self.sweep() * t;
+            let radius = self.radius().value_m();
+
+            point[a] = center_a + radius * angle.cos();
+            point[b] = center_b + radius * angle.sin();
+        }
+
+        point.map(Length::from_value_m)
+    }
+}
+
+/// How a segment moves from its start to its end
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Motion {
+    /// A move at the maximum rate (G0)
+    Rapid,
+
+    /// A straight move at the feed rate (G1)
+    Linear,
+
+    /// A circular or helical move at the feed rate (G2/G3)
+    ///
+    /// Start and end point are equal for a full circle.
+    Arc {
+        center: Point,
+        plane: Plane,
+
+        /// Looking at the plane from the positive end of the third axis
+        clockwise: bool,
+    },
+
+    /// Waiting in place, after the machine has stopped (G4)
+    ///
+    /// Start and end point are equal.
+    Dwell(Duration),
+}
+
+/// The plane that arcs lie in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Plane {
+    /// G17
+    Xy,
+
+    /// G18
+    Zx,
+
+    /// G19
+    Yz,
+}
+
+impl Plane {
+    /// The indices of the two axes of the plane, then of the third axis
+    ///
+    /// The first two are in the order that makes counter-clockwise positive.
+    pub fn axes(&self) -> [usize; 3] {
+        match self {
+            Plane::Xy => [0, 1, 2],
+            Plane::Zx => [2, 0, 1],
+            Plane::Yz => [1, 2, 0],
+        }
+    }
+}
+
+/// An error loading a [`Toolpath`]
+#[derive(Debug)]
+pub enum Error {
+    Read {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: gcode::ParseError,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Read { path, source } => {
+                write!(f, "Can't read `{}`: {source}", path.display())
+            }
+            Error::Parse { path, source } => {
+                write!(f, "Can't parse `{}`: {source}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Read { source, .. } => Some(source),
+            Error::Parse { source, .. } => Some(source),
+        }
+    }
+}
@@ -0,0 +1,528 @@
+//! Motion planning, the way GRBL does it
+//!
+//! The programmed feed is only what the machine moves at when it gets the
+//! chance. GRBL splits arcs into short lines, slows down for the corners
+//! between lines according to its junction deviation, and plans acceleration
+//! over a buffer of the next few lines, so it can always stop at the end of
+//! that. This does the same, to see how long a program really takes, and how
+//! close each move gets to its feed.
+//!
+//! GRBL itself only does trapezoidal velocity profiles. Other controllers also
+//! limit jerk, which takes longer, but is easier on the machine.
+
+use std::time::Duration;
+
+use crate::{
+    machine::limits::AxisLimits,
+    physics::{Acceleration, Jerk, Length, Speed},
+};
+
+use super::{Motion, Point, Segment, Toolpath};
+
+pub struct Planner {
+    /// The maximum rate of each axis (`$110`-`$112` in GRBL)
+    pub max_rate: [Speed; 3],
+
+    /// The maximum acceleration of each axis (`$120`-`$122` in GRBL)
+    pub max_acceleration: [Acceleration; 3],
+
+    /// How far the path may cut a corner between two lines (`$11` in GRBL)
+    pub junction_deviation: Length,
+
+    /// How far the lines that an arc is split into may deviate from it (`$12`
+    /// in GRBL)
+    pub arc_tolerance: Length,
+
+    /// The number of lines that the planner looks ahead
+    pub buffer_size: usize,
+
+    pub profile: Profile,
+}
+
+impl Planner {
+    /// Plan with the limits from [`limits::grbl_settings`], and GRBL's defaults
+    /// for everything else
+    ///
+    /// [`limits::grbl_settings`]: crate::machine::limits::grbl_settings
+    pub fn new(limits: &[AxisLimits], profile: Profile) -> Self {
+        Self {
+            max_rate: [0, 1, 2].map(|i| limits[i].max_rapid_velocity.speed),
+            max_acceleration: [0, 1, 2].map(|i| limits[i].max_acceleration),
+            junction_deviation: Length::from_value_mm(0.01),
+            arc_tolerance: Length::from_value_mm(0.002),
+            // `BLOCK_BUFFER_SIZE` is 16 on the Arduino Uno, and GRBL keeps
+            // one of those free.
+            buffer_size: 15,
+            profile,
+        }
+    }
+
+    pub fn plan(&self, toolpath: &Toolpath) -> Plan {
+        let blocks = self.blocks(toolpath);
+        let n = blocks.len();
+
+        // When a block starts, the buffer reaches `buffer_size` blocks ahead,
+        // and the machine needs to be able to stop at the end of that. Going
+        // backwards from there gives the fastest the block can start at.
+        let max_entry = (0..n)
+            .map(|k| {
+                let end = (k + self.buffer_size).min(n);
+                blocks[k..end].iter().rev().fold(0., |exit, block| {
+                    block.max_entry.min(block.reachable(exit))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Going forward, each block starts as fast as the one before it could
+        // accelerate to. The program ends at rest.
+        let mut entry = vec![0.; n + 1];
+        for k in 0..n {
+            entry[k + 1] = if k + 1 < n {
+                max_entry[k + 1].min(blocks[k].reachable(entry[k]))
+            } else {
+                0.
+            };
+        }
+
+        let mut segments = toolpath
+            .segments
+            .iter()
+            .map(|segment| {
+                let dwell = match segment.motion {
+                    Motion::Dwell(duration) => duration,
+                    _ => Duration::ZERO,
+                };
+
+                PlannedSegment {
+                    line: segment.line,
+                    length: segment.length(),
+                    programmed: segment.is_feed().then_some(segment.feed),
+                    peak: Speed::from_value_m_per_s(0.),
+                    time: dwell,
+                    unaccelerated_time: dwell,
+                }
+            })
+            .collect::<Vec<_>>();
+        for (k, block) in blocks.iter().enumerate() {
+            let (time, peak) = block.time(entry[k], entry[k + 1]);
+
+            let segment = &mut segments[block.segment];
+            segment.time += Duration::from_secs_f64(time);
+            segment.unaccelerated_time +=
+                Duration::from_secs_f64(block.length / block.nominal);
+            if peak > segment.peak.value_m_per_s() {
+                segment.peak = Speed::from_value_m_per_s(peak);
+            }
+        }
+
+        Plan { segments }
+    }
+
+    /// Split the toolpath into the straight lines that GRBL plans with
+    fn blocks(&self, toolpath: &Toolpath) -> Vec<Block> {
+        let max_rate = self.max_rate.map(|rate| rate.value_m_per_s());
+        let max_acceleration = self
+            .max_acceleration
+            .map(|acceleration| acceleration.value_m_per_s2());
+
+        let mut blocks = Vec::new();
+        let mut previous: Option<([f64; 3], f64)> = None;
+
+        for (i, segment) in toolpath.segments.iter().enumerate() {
+            // GRBL waits for the machine to stop before changing the spindle,
+            // and a tool change stops the program.
+            if let Some(before) =
+                i.checked_sub(1).map(|i| &toolpath.segments[i])
+            {
+                if before.spindle_speed != segment.spindle_speed
+                    || before.tool != segment.tool
+                {
+                    previous = None;
+                }
+            }
+
+            let feed = match segment.motion {
+                Motion::Rapid => f64::INFINITY,
+                Motion::Linear | Motion::Arc { .. } => {
+                    segment.feed.value_m_per_s()
+                }
+                // GRBL waits for the machine to stop before dwelling too.
+                Motion::Dwell(_) => {
+                    previous = None;
+                    continue;
+                }
+            };
+
+            for pair in self.points(segment).windows(2) {
+                let delta = [0, 1, 2].map(|i| pair[1][i] - pair[0][i]);
+                let length = norm(delta);
+                if length < 1e-9 {
+                    continue;
+                }
+                let unit = delta.map(|d| d / length);
+
+                let nominal = feed.min(limit_by_axes(max_rate, unit));
+                let acceleration = limit_by_axes(max_acceleration, unit);
+                let jerk = match self.profile {
+                    Profile::Trapezoidal => f64::INFINITY,
+                    Profile::JerkLimited(jerk) => {
+                        limit_by_axes([jerk.value_m_per_s3(); 3], unit)
+                    }
+                };
+
+                let max_entry = match previous {
+                    Some((previous_unit, previous_nominal)) => self
+                        .junction_speed(previous_unit, unit, max_acceleration)
+                        .min(nominal)
+                        .min(previous_nominal),
+                    None => 0.,
+                };
+
+                blocks.push(Block {
+                    segment: i,
+                    length,
+                    nominal,
+                    acceleration,
+                    jerk,
+                    max_entry,
+                });
+                previous = Some((unit, nominal));
+            }
+        }
+
+        blocks
+    }
+
+    /// The points that a segment is split into, in m
+    fn points(&self, segment: &Segment) -> Vec<[f64; 3]> {
+        let to_m = |point: Point| point.map(|c| c.value_m());
+
+        let Motion::Arc { .. } = segment.motion else {
+            return vec![to_m(segment.start), to_m(segment.end)];
+        };
+
+        // Same as `mc_arc` in GRBL.
+        let radius = segment.radius().value_m();
+        let tolerance = self.arc_tolerance.value_m();
+        let n = ((0.5 * segment.sweep() * radius).abs()
+            / (tolerance * (2. * radius - tolerance)).sqrt())
+        .floor()
+        .max(1.);
+
+        (0..=n as usize)
+            .map(|i| to_m(segment.point_at(i as f64 / n)))
+            .collect()
+    }
+
+    /// The maximum speed through the corner between two lines, in m/s
+    ///
+    /// That's GRBL's junction deviation: the speed at which the centripetal
+    /// acceleration on a circle that deviates `junction_deviation` from the
+    /// corner is the maximum acceleration.
+    fn junction_speed(
+        &self,
+        previous: [f64; 3],
+        unit: [f64; 3],
+        max_acceleration: [f64; 3],
+    ) -> f64 {
+        let cos_theta = -(0..3).map(|i| previous[i] * unit[i]).sum::<f64>();
+        if cos_theta > 0.999999 {
+            // Reversing direction.
+            return 0.;
+        }
+        if cos_theta < -0.999999 {
+            // Going straight on.
+            return f64::INFINITY;
+        }
+
+        let junction = [0, 1, 2].map(|i| unit[i] - previous[i]);
+        let junction = junction.map(|j| j / norm(junction));
+        let acceleration = limit_by_axes(max_acceleration, junction);
+
+        let sin_theta_d2 = (0.5 * (1. - cos_theta)).sqrt();
+        (acceleration * self.junction_deviation.value_m() * sin_theta_d2
+            / (1. - sin_theta_d2))
+            .sqrt()
+    }
+}
+
+/// The shape of the velocity profile
+#[derive(Clone, Copy)]
+pub enum Profile {
+    /// Acceleration switches on and off instantly, as in GRBL
+    Trapezoidal,
+
+    /// Acceleration ramps up and down at the given jerk, per axis
+    JerkLimited(Jerk),
+}
+
+/// The jerk to compare jerk-limited planning at
+pub fn default_jerk() -> Jerk {
+    // There's no data for the machine yet. This takes a tenth of a second to
+    // ramp up to 1 m/s², which should be gentle on the frame, without being
+    // overly slow.
+    Jerk::from_value_m_per_s3(10.)
+}
+
+/// A planned program
+pub struct Plan {
+    pub segments: Vec<PlannedSegment>,
+}
+
+impl Plan {
+    pub fn cycle_time(&self) -> Duration {
+        self.segments.iter().map(|segment| segment.time).sum()
+    }
+
+    /// The cycle time, if the machine could change speed instantly
+    pub fn unaccelerated_time(&self) -> Duration {
+        self.segments
+            .iter()
+            .map(|segment| segment.unaccelerated_time)
+            .sum()
+    }
+}
+
+/// How a segment of the program is executed
+pub struct PlannedSegment {
+    /// The line of the program that the move comes from
+    pub line: usize,
+
+    pub length: Length,
+
+    /// The programmed feed, for moves that aren't rapids or dwells
+    pub programmed: Option<Speed>,
+
+    /// The highest speed reached along the move
+    pub peak: Speed,
+
+    pub time: Duration,
+
+    /// The time that the move would take at its feed, or the rates of the
+    /// axes, without accelerating
+    pub unaccelerated_time: Duration,
+}
+
+impl PlannedSegment {
+    /// The average speed achieved along the move
+    pub fn average(&self) -> Speed {
+        if self.time.is_zero() {
+            return Speed::from_value_m_per_s(0.);
+        }
+        Speed::from_value_m_per_s(
+            self.length.value_m() / self.time.as_secs_f64(),
+        )
+    }
+}
+
+/// A straight line, as planned by GRBL
+///
+/// All values are in SI units, as the planner works with the plain numbers.
+struct Block {
+    /// The index of the segment that the block is part of
+    segment: usize,
+
+    length: f64,
+
+    /// The speed the block moves at, if it can
+    nominal: f64,
+
+    acceleration: f64,
+
+    /// Infinite, for trapezoidal profiles
+    jerk: f64,
+
+    /// The maximum speed at the junction with the previous block
+    max_entry: f64,
+}
+
+impl Block {
+    /// The time it takes to change speed
+    fn change_time(&self, from: f64, to: f64) -> f64 {
+        let (a, j) = (self.acceleration, self.jerk);
+        let change = (to - from).abs();
+
+        // With limited jerk, the acceleration ramps up and down. If the change
+        // is small, it never reaches the maximum.
+        if change >= a * a / j {
+            change / a + a / j
+        } else {
+            2. * (change / j).sqrt()
+        }
+    }
+
+    /// The distance it takes to change speed
+    ///
+    /// The profile is symmetric, so the average speed is halfway between.
+    fn change_distance(&self, from: f64, to: f64) -> f64 {
+        (from + to) / 2. * self.change_time(from, to)
+    }
+
+    /// The highest speed at one end of the block, given the other
+    fn reachable(&self, from: f64) -> f64 {
+        let upper = (from * from + 2. * self.acceleration * self.length).sqrt();
+        if self.jerk.is_infinite() {
+            return upper;
+        }
+        bisect(from, upper, |to| {
+            self.change_distance(from, to) <= self.length
+        })
+    }
+
+    /// The time the block takes, and the highest speed it reaches
+    fn time(&self, entry: f64, exit: f64) -> (f64, f64) {
+        let low = entry.max(exit);
+        let upper = ((2. * self.acceleration * self.length
+            + entry * entry
+            + exit * exit)
+            / 2.)
+            .sqrt()
+            .min(self.nominal)
+            .max(low);
+        let peak = if self.jerk.is_infinite() {
+            upper
+        } else {
+            bisect(low, upper, |peak| {
+                self.change_distance(entry, peak)
+                    + self.change_distance(peak, exit)
+                    <= self.length
+            })
+        };
+        if peak <= 0. {
+            return (0., 0.);
+        }
+
+        let cruise = (self.length
+            - self.change_distance(entry, peak)
+            - self.change_distance(peak, exit))
+        .max(0.);
+        let time = self.change_time(entry, peak)
+            + self.change_time(peak, exit)
+            + cruise / peak;
+
+        (time, peak)
+    }
+}
+
+/// Limit a value that's given per axis, along a direction
+///
+/// Same as `limit_value_by_axis_maximum` in GRBL.
+fn limit_by_axes(max: [f64; 3], unit: [f64; 3]) -> f64 {
+    (0..3)
+        .filter(|&i| unit[i] != 0.)
+        .map(|i| max[i] / unit[i].abs())
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Find the highest value between `low` and `high` that's still `ok`
+fn bisect(mut low: f64, mut high: f64, ok: impl Fn(f64) -> bool) -> f64 {
+    for _ in 0..50 {
+        let mid = (low + high) / 2.;
+        if ok(mid) {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        physics::{Acceleration, Jerk, Length, Speed},
+        toolpath::{gcode::parse, Toolpath},
+    };
+
+    use super::{Planner, Profile};
+
+    const ACCELERATION: f64 = 1.;
+
+    #[test]
+    fn long_move_follows_a_trapezoid() {
+        // 100 mm at 0.1 m/s, which is reached after 5 mm.
+        let plan =
+            planner(Profile::Trapezoidal).plan(&toolpath("G1 X100 F6000"));
+
+        let feed = 0.1;
+        let expected = feed / ACCELERATION + 0.1 / feed;
+        assert_close(plan.cycle_time().as_secs_f64(), expected);
+        assert_close(plan.segments[0].peak.value_m_per_s(), feed);
+        assert_close(plan.unaccelerated_time().as_secs_f64(), 0.1 / feed);
+    }
+
+    #[test]
+    fn short_move_follows_a_triangle() {
+        // 1 mm isn't enough to reach the feed, so the move accelerates for
+        // half of it, then decelerates.
+        let plan = planner(Profile::Trapezoidal).plan(&toolpath("G1 X1 F6000"));
+
+        let length = 0.001;
+        assert_close(
+            plan.cycle_time().as_secs_f64(),
+            2. * (length / ACCELERATION).sqrt(),
+        );
+        assert_close(
+            plan.segments[0].peak.value_m_per_s(),
+            (ACCELERATION * length).sqrt(),
+        );
+    }
+
+    #[test]
+    fn dwell_adds_its_time() {
+        let planner = planner(Profile::Trapezoidal);
+        let without = planner.plan(&toolpath("G1 X100 F6000\nG1 X200"));
+        let with = planner.plan(&toolpath("G1 X100 F6000\nG4 P2\nG1 X200"));
+
+        // The machine has to stop for the dwell, which costs a deceleration
+        // and an acceleration on top of the dwell itself.
+        let stop = 0.1 / ACCELERATION;
+        assert_close(
+            with.cycle_time().as_secs_f64(),
+            without.cycle_time().as_secs_f64() + 2. + stop,
+        );
+        assert!(with.segments[1].programmed.is_none());
+    }
+
+    #[test]
+    fn limiting_jerk_takes_longer() {
+        let program = toolpath("G1 X100 F6000");
+        let trapezoidal = planner(Profile::Trapezoidal).plan(&program);
+        let jerk_limited =
+            planner(Profile::JerkLimited(Jerk::from_value_m_per_s3(10.)))
+                .plan(&program);
+
+        // Each change of speed takes a/j longer, but covers half of that
+        // distance at the feed, which saves cruising.
+        assert_close(
+            jerk_limited.cycle_time().as_secs_f64(),
+            trapezoidal.cycle_time().as_secs_f64() + ACCELERATION / 10.,
+        );
+    }
+
+    fn planner(profile: Profile) -> Planner {
+        Planner {
+            max_rate: [Speed::from_value_m_per_s(1.); 3],
+            max_acceleration: [Acceleration::from_value_m_per_s2(ACCELERATION);
+                3],
+            junction_deviation: Length::from_value_mm(0.01),
+            arc_tolerance: Length::from_value_mm(0.002),
+            buffer_size: 15,
+            profile,
+        }
+    }
+
+    fn toolpath(program: &str) -> Toolpath {
+        Toolpath {
+            segments: parse(program).unwrap(),
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+    }
+}
@@ -0,0 +1,533 @@
+//! A simulation of the stock, to see what each move actually cuts
+//!
+//! G-code doesn't say anything about the stock. By convention, the origin of a
+//! program is on top of it though, so let's assume a block with its top at
+//! z = 0, under everything that the program cuts.
+//!
+//! The stock is a grid of dexels: columns of material along z, one per cell. A
+//! tool pointing down only ever removes material from the top of a column, so
+//! each column is a single interval, from the bottom of the stock to its
+//! height. Moves sweep the tool along them, in steps of half a cell.
+
+use std::io::Write as _;
+
+use crate::{
+    physics::{Diameter, Length, Volume},
+    stl::Mesh,
+    tools::Tool,
+};
+
+use super::{Point, Segment, Toolpath};
+
+pub struct Stock {
+    /// The corner of the grid with the lowest x and y, in mm
+    origin: [f64; 2],
+
+    /// The width of a cell, in mm
+    cell: f64,
+
+    /// The number of cells along x and y
+    cells: [usize; 2],
+
+    /// The bottom of the stock, in mm
+    bottom: f64,
+
+    /// The height of the material in each cell, in mm, row by row along x
+    heights: Vec<f64>,
+
+    /// The radius of the collet nut, in mm
+    collet_nut: f64,
+}
+
+impl Stock {
+    /// Create a block under everything that the program cuts
+    ///
+    /// When a program faces the stock, the tool overhangs its edges, so the
+    /// block comes out a bit larger than the real stock. That errs on the side
+    /// of more engagement. It also reaches as far as the shanks and the collet
+    /// nut do, so they can run into the walls next to a cut.
+    ///
+    /// A pocket can't be told apart from a cut through the stock, without
+    /// knowing how thick it is. Let's leave a millimeter under the deepest cut,
+    /// so it always looks like a pocket.
+    pub fn new(
+        toolpath: &Toolpath,
+        tools: &[Tool],
+        collet_nut: Diameter,
+        cell: Length,
+    ) -> Self {
+        let cell = cell.value_mm();
+        let collet_nut = collet_nut.to_length().value_mm() / 2.;
+
+        let mut min = [f64::INFINITY; 2];
+        let mut max = [f64::NEG_INFINITY; 2];
+        let mut deepest = 0f64;
+        for segment in &toolpath.segments {
+            if !segment.is_feed() {
+                continue;
+            }
+            let Some(tool) =
+                segment.tool.and_then(|number| super::tool(number, tools))
+            else {
+                continue;
+            };
+            let radius = reach(tool).max(collet_nut);
+
+            for point in points(segment, cell) {
+                if point[2] >= 0. {
+                    continue;
+                }
+                for i in 0..2 {
+                    min[i] = min[i].min(point[i] - radius);
+                    max[i] = max[i].max(point[i] + radius);
+                }
+                deepest = deepest.min(point[2]);
+            }
+        }
+
+        if min[0] > max[0] {
+            return Self {
+                origin: [0.; 2],
+                cell,
+                cells: [0; 2],
+                bottom: -1.,
+                heights: Vec::new(),
+                collet_nut,
+            };
+        }
+
+        let cells = [0, 1].map(|i| ((max[i] - min[i]) / cell).ceil() as usize);
+        Self {
+            origin: min,
+            cell,
+            cells,
+            bottom: deepest - 1.,
+            heights: vec![0.; cells[0] * cells[1]],
+            collet_nut,
+        }
+    }
+
+    /// The size of the block, along x and y
+    pub fn size(&self) -> [Length; 2] {
+        self.cells
+            .map(|n| Length::from_value_mm(n as f64 * self.cell))
+    }
+
+    /// The volume of the material that's left
+    pub fn volume(&self) -> Volume {
+        let height = self
+            .heights
+            .iter()
+            .map(|height| height - self.bottom)
+            .fold(0., |a, b| a + b);
+        Volume::from_value_mm3(height * self.cell * self.cell)
+    }
+
+    /// Remove the material that a move cuts
+    pub fn cut(&mut self, segment: &Segment, tool: &Tool) -> Cut {
+        let radius = tool.diameter.to_length().value_mm() / 2.;
+        let cutting_edge = tool.length_cutting_edge.value_mm();
+        let stick_out = tool.stick_out().value_mm();
+
+        // Past the shank, there's the collet nut, which is wider still.
+        let reach = reach(tool);
+        let scan = reach.max(self.collet_nut);
+
+        let mut cut = Cut {
+            engagement: Engagement::none(),
+            max_engagement: Engagement::none(),
+            removed: Volume::from_value_m3(0.),
+            shank_collision: None,
+            collet_collision: None,
+        };
+        let mut removed = 0.;
+        let mut collision = 0f64;
+        let mut collet_collision = 0f64;
+
+        let mut previous = to_mm(segment.start);
+        for point in points(segment, self.cell).skip(1) {
+            let direction = [point[0] - previous[0], point[1] - previous[1]];
+            let distance = direction[0].hypot(direction[1]);
+            let rising = point[2] > previous[2];
+            previous = point;
+
+            // Only the front half of the tool cuts, and the width of the cut
+            // is measured across the direction of the move. Plunges don't have
+            // a direction, so the whole end of the tool cuts, and any
+            // direction across is as good as another.
+            let (forward, across) = if distance > 1e-9 {
+                let forward = direction.map(|d| d / distance);
+                (forward, [-forward[1], forward[0]])
+            } else {
+                ([0.; 2], [1., 0.])
+            };
+
+            let tip = point[2];
+            let shank = tip + cutting_edge;
+            let nut = tip + stick_out;
+            let mut depth = 0f64;
+            let mut width = [f64::INFINITY, f64::NEG_INFINITY];
+
+            let range = |i: usize| {
+                let cell = |offset: f64| {
+                    ((point[i] + offset - self.origin[i]) / self.cell)
+                        .floor()
+                        .clamp(0., self.cells[i] as f64)
+                        as usize
+                };
+                cell(-scan)..cell(scan + self.cell)
+            };
+            for y in range(1) {
+                for x in range(0) {
+                    let dx = self.origin[0] + (x as f64 + 0.5) * self.cell
+                        - point[0];
+                    let dy = self.origin[1] + (y as f64 + 0.5) * self.cell
+                        - point[1];
+                    let distance_squared = dx * dx + dy * dy;
+                    if distance_squared > scan * scan {
+                        continue;
+                    }
+
+                    // Like the cutter, shank and nut only run into what's in
+                    // front of them. Going straight up, that's nothing.
+                    let height = &mut self.heights[y * self.cells[0] + x];
+                    let in_front = dx * forward[0] + dy * forward[1] >= 0.;
+                    let straight_up = rising && distance <= 1e-9;
+                    if in_front && !straight_up {
+                        if distance_squared <= reach * reach && *height > shank
+                        {
+                            collision = collision.max(*height - shank);
+                        }
+                        if distance_squared <= self.collet_nut * self.collet_nut
+                            && *height > nut
+                        {
+                            collet_collision =
+                                collet_collision.max(*height - nut);
+                        }
+                    }
+                    if distance_squared > radius * radius || *height <= tip {
+                        continue;
+                    }
+
+                    if in_front {
+                        let lateral = dx * across[0] + dy * across[1];
+                        depth = depth.max(*height - tip);
+                        width = [width[0].min(lateral), width[1].max(lateral)];
+                    }
+                    removed += *height - tip;
+                    *height = tip;
+                }
+            }
+
+            // Anything past the cutting edge isn't cut, but rubbed by the
+            // shank. That's a collision, not engagement.
+            if depth > 0. {
+                let axial = Length::from_value_mm(depth.min(cutting_edge));
+                let radial = Length::from_value_mm(
+                    (width[1] - width[0] + self.cell).min(radius * 2.),
+                );
+
+                if axial.value_mm() * radial.value_mm()
+                    > cut.engagement.axial.value_mm()
+                        * cut.engagement.radial.value_mm()
+                {
+                    cut.engagement = Engagement { axial, radial };
+                }
+                if axial > cut.max_engagement.axial {
+                    cut.max_engagement.axial = axial;
+                }
+                if radial > cut.max_engagement.radial {
+                    cut.max_engagement.radial = radial;
+                }
+            }
+        }
+
+        cut.removed = Volume::from_value_mm3(removed * self.cell * self.cell);
+        if collision > 0. {
+            cut.shank_collision = Some(Length::from_value_mm(collision));
+        }
+        if collet_collision > 0. {
+            cut.collet_collision =
+                Some(Length::from_value_mm(collet_collision));
+        }
+        cut
+    }
+
+    /// Export the heights as a grayscale image, in the PGM format
+    ///
+    /// That's simple enough to write without a dependency, and most image
+    /// viewers open it. White is the top of the stock, black its bottom, and
+    /// up in the image is +y.
+    pub fn to_pgm(&self) -> Vec<u8> {
+        let [width, height] = self.cells;
+        let mut image = Vec::new();
+        write!(image, "P5\n{width} {height}\n255\n").unwrap();
+
+        for y in (0..height).rev() {
+            for x in 0..width {
+                let z = self.heights[y * width + x];
+                let gray = (z - self.bottom) / -self.bottom * 255.;
+                image.push(gray.round() as u8);
+            }
+        }
+
+        image
+    }
+
+    /// Export the stock as a closed mesh
+    ///
+    /// Each dexel is a box. Where neighboring boxes along x are at the same
+    /// height, their tops are merged.
+    pub fn to_mesh(&self) -> Mesh {
+        let [nx, ny] = self.cells;
+        let x = |i: usize| self.origin[0] + i as f64 * self.cell;
+        let y = |j: usize| self.origin[1] + j as f64 * self.cell;
+        let z = |i: usize, j: usize| self.heights[j * nx + i];
+        let bottom = self.bottom;
+
+        let mut triangles = Vec::new();
+        if nx == 0 || ny == 0 {
+            return Mesh { triangles };
+        }
+
+        // The corners of each face go counter-clockwise, seen from outside.
+        let mut quad = |corners: [[f64; 3]; 4]| {
+            triangles.push([corners[0], corners[1], corners[2]]);
+            triangles.push([corners[0], corners[2], corners[3]]);
+        };
+
+        for j in 0..ny {
+            let mut i = 0;
+            while i < nx {
+                let mut end = i + 1;
+                while end < nx && z(end, j) == z(i, j) {
+                    end += 1;
+                }
+                let h = z(i, j);
+                quad([
+                    [x(i), y(j), h],
+                    [x(end), y(j), h],
+                    [x(end), y(j + 1), h],
+                    [x(i), y(j + 1), h],
+                ]);
+                i = end;
+            }
+        }
+        quad([
+            [x(0), y(0), bottom],
+            [x(0), y(ny), bottom],
+            [x(nx), y(ny), bottom],
+            [x(nx), y(0), bottom],
+        ]);
+
+        // The walls between dexels, and around the stock. Outside of it, the
+        // height is that of the bottom. The walls face from the higher side
+        // towards the lower one.
+        let mut wall = |a: [f64; 2], b: [f64; 2], before: f64, after: f64| {
+            if before == after {
+                return;
+            }
+            let (from, to) = (before.min(after), before.max(after));
+            let mut corners = [
+                [a[0], a[1], from],
+                [b[0], b[1], from],
+                [b[0], b[1], to],
+                [a[0], a[1], to],
+            ];
+            if after > before {
+                corners.reverse();
+            }
+            quad(corners);
+        };
+        for j in 0..ny {
+            for i in 0..=nx {
+                let before = if i == 0 { bottom } else { z(i - 1, j) };
+                let after = if i == nx { bottom } else { z(i, j) };
+                wall([x(i), y(j)], [x(i), y(j + 1)], before, after);
+            }
+        }
+        for i in 0..nx {
+            for j in 0..=ny {
+                let before = if j == 0 { bottom } else { z(i, j - 1) };
+                let after = if j == ny { bottom } else { z(i, j) };
+                wall([x(i + 1), y(j)], [x(i), y(j)], before, after);
+            }
+        }
+
+        Mesh { triangles }
+    }
+}
+
+/// What a move does to the stock
+pub struct Cut {
+    /// The engagement where the move cuts the largest cross-section
+    pub engagement: Engagement,
+
+    /// The largest axial and radial depth of cut along the move
+    ///
+    /// They don't need to be at the same place.
+    pub max_engagement: Engagement,
+
+    /// The volume of the material that the move removes
+    pub removed: Volume,
+
+    /// How far the material reaches past the cutting edge, if it does
+    ///
+    /// That's where the shank rubs against the material, or worse.
+    pub shank_collision: Option<Length>,
+
+    /// How far the material reaches past the part of the tool that sticks out
+    /// of the collet, if it does
+    ///
+    /// That's where the collet nut runs into it.
+    pub collet_collision: Option<Length>,
+}
+
+impl Cut {
+    pub fn is_cutting(&self) -> bool {
+        self.engagement.axial.value_mm() > 0.
+    }
+}
+
+/// How deep and wide a tool cuts
+#[derive(Clone, Copy, Debug)]
+pub struct Engagement {
+    /// The axial depth of cut
+    pub axial: Length,
+
+    /// The radial depth of cut, across the direction of the move
+    pub radial: Length,
+}
+
+impl Engagement {
+    pub fn none() -> Self {
+        Self {
+            axial: Length::from_value_mm(0.),
+            radial: Length::from_value_mm(0.),
+        }
+    }
+}
+
+/// How far from its axis the tool reaches, in mm
+///
+/// Small tools have a shank that's wider than the cutter. That can hit material
+/// that the cutter never touches.
+fn reach(tool: &Tool) -> f64 {
+    tool.diameter
+        .to_length()
+        .value_mm()
+        .max(tool.shank_diameter().to_length().value_mm())
+        / 2.
+}
+
+/// Points along a segment, half a cell apart, in mm
+fn points(segment: &Segment, cell: f64) -> impl Iterator<Item = [f64; 3]> + '_ {
+    let steps = (segment.length().value_mm() / (cell / 2.)).ceil().max(1.);
+    (0..=steps as usize).map(move |i| to_mm(segment.point_at(i as f64 / steps)))
+}
+
+fn to_mm(point: Point) -> [f64; 3] {
+    point.map(|coordinate| coordinate.value_mm())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        physics::{Diameter, Length},
+        toolpath::{gcode::parse, Toolpath},
+        tools::Tool,
+    };
+
+    use super::{Cut, Stock};
+
+    /// A 2 mm deep slot along x, then a shorter second pass next to it, half
+    /// a diameter over
+    ///
+    /// The second pass stays clear of the end of the slot, where the round end
+    /// leaves more material.
+    const PROGRAM: &str = "T1 M6\nS10000 M3\nG0 X0 Y0 Z5\nG1 Z-2 F300\n\
+        G1 X40\nG0 Z5\nG0 X0 Y3\nG1 Z-2\nG1 X30\nG0 Z5";
+
+    /// The same as the spindle's
+    const COLLET_NUT_MM: f64 = 19.;
+
+    #[test]
+    fn slot_engages_the_full_diameter() {
+        let slot = cut(5, tool(15., 50.));
+
+        assert_close(slot.engagement.axial.value_mm(), 2., 1e-9);
+        assert_close(slot.engagement.radial.value_mm(), 6., 1e-9);
+
+        // The plunge already took out a circle at the start, and the slot
+        // leaves one at its end.
+        assert_close(slot.removed.value_mm3(), 40. * 6. * 2., 5.);
+        assert!(slot.shank_collision.is_none());
+    }
+
+    #[test]
+    fn second_pass_engages_what_the_slot_left() {
+        let pass = cut(9, tool(15., 50.));
+
+        assert_close(pass.engagement.axial.value_mm(), 2., 1e-9);
+        // The edge of the slot is only as sharp as the cells are wide.
+        assert_close(pass.engagement.radial.value_mm(), 3., 0.3);
+    }
+
+    #[test]
+    fn material_past_the_cutting_edge_hits_the_shank() {
+        let slot = cut(5, tool(1.5, 50.));
+
+        assert_close(slot.engagement.axial.value_mm(), 1.5, 1e-9);
+        let collision = slot.shank_collision.unwrap().value_mm();
+        assert_close(collision, 0.5, 1e-9);
+        assert!(slot.collet_collision.is_none());
+    }
+
+    #[test]
+    fn material_past_the_stick_out_hits_the_collet_nut() {
+        // Only 1 mm sticks out of the collet, and that's all cutting edge.
+        let slot = cut(5, tool(1., 19.));
+
+        let collision = slot.collet_collision.unwrap().value_mm();
+        assert_close(collision, 1., 1e-9);
+    }
+
+    /// Cut the stock with the moves of the program, up to the given line
+    ///
+    /// Returns what the move on that line cuts.
+    fn cut(line: usize, tool: Tool) -> Cut {
+        let toolpath = Toolpath {
+            segments: parse(PROGRAM).unwrap(),
+        };
+        let tools = [tool];
+        let mut stock = Stock::new(
+            &toolpath,
+            &tools,
+            Diameter::from_length(Length::from_value_mm(COLLET_NUT_MM)),
+            Length::from_value_mm(0.1),
+        );
+
+        let mut cut = None;
+        for segment in toolpath.segments.iter().take_while(|s| s.line <= line) {
+            cut = Some(stock.cut(segment, &tools[0]));
+        }
+        cut.unwrap()
+    }
+
+    /// A 6 mm end mill with the given lengths of cutting edge and in total
+    fn tool(cutting_edge_mm: f64, total_mm: f64) -> Tool {
+        Tool {
+            diameter: Diameter::from_length(Length::from_value_mm(6.)),
+            length_cutting_edge: Length::from_value_mm(cutting_edge_mm),
+            length_total: Length::from_value_mm(total_mm),
+            num_flutes: 2.,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{actual} != {expected}"
+        );
+    }
+}
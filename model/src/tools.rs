@@ -1,6 +1,12 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    f64::consts::{PI, TAU},
+    fmt,
+};
 
-use crate::physics::{Diameter, Force, Length, RotationalSpeed, Speed, Torque};
+use crate::physics::{
+    Diameter, Force, Length, RotationalSpeed, Speed, Stiffness, Torque,
+};
 
 #[derive(Debug)]
 pub struct Tool {
@@ -254,6 +260,61 @@ impl Tool {
         Length::from_value_mm(length_mm)
     }
 
+    /// The feed rate, at the given spindle speed
+    pub fn feed(&self, rotational_speed: RotationalSpeed) -> Speed {
+        Speed::from_value_m_per_min(
+            rotational_speed.value_rpm()
+                * self.num_flutes
+                * self.feed_per_tooth().value_m(),
+        )
+    }
+
+    /// The diameter of the tool's shank
+    pub fn shank_diameter(&self) -> Diameter {
+        // All the tools below 3 mm in the list have a 1/8" shank. The others
+        // have a shank of the same diameter as the cutter.
+        if self.diameter.to_length().value_mm() < 3. {
+            Diameter::from_length(Length::from_value_mm(3.175))
+        } else {
+            self.diameter
+        }
+    }
+
+    /// The length of the tool that sticks out of the collet
+    pub fn stick_out(&self) -> Length {
+        // An ER16 collet is 27.5 mm long, and should grip at least 2/3 of
+        // that. Whatever isn't gripped sticks out of it.
+        let clamped = 18.;
+
+        Length::from_value_mm(
+            (self.length_total.value_mm() - clamped)
+                .max(self.length_cutting_edge.value_mm()),
+        )
+    }
+
+    /// The stiffness of the tool, against a force at its tip
+    pub fn stiffness(&self) -> Stiffness {
+        // Solid carbide.
+        let e = 600_000_000_000.; // Pascal
+
+        // The flutes take away material, so the fluted part is weaker than
+        // its diameter suggests. 80% of the diameter is a common rule of
+        // thumb for the equivalent diameter.
+        let d = self.diameter.to_length().value_m();
+        let d_shank = self.shank_diameter().to_length().value_m();
+        let i_flutes = PI * (0.8 * d).powi(4) / 64.;
+        let i_shank = PI * d_shank.powi(4) / 64.;
+
+        // A cantilever with a stepped section, loaded at its tip. The fluted
+        // part is the outer `a`, the shank the rest.
+        let a = self.length_cutting_edge.value_m();
+        let l = self.stick_out().value_m();
+        let compliance = a.powi(3) / (3. * e * i_flutes)
+            + (l.powi(3) - a.powi(3)) / (3. * e * i_shank);
+
+        Stiffness::from_compliance_m_per_n(compliance)
+    }
+
     pub fn tangential_cutting_force(&self) -> (Force, Torque) {
         // This article talks about tangential cutting force:
         // https://www.ctemag.com/news/articles/understanding-tangential-cutting-force-when-milling
@@ -268,12 +329,6 @@ impl Tool {
         // Ef: engagement factor of workpiece material
         // Tf: cutting tool wear factor
         //
-        // Wikipedia has an article on ultimate tensile strength:
-        // https://en.wikipedia.org/wiki/Ultimate_tensile_strength
-        //
-        // According to the table in there, this is the value for aluminium:
-        let sigma = 483_000_000.; // Pascal
-
         // The cross-sectional area of the uncut chip depends on axial depth
         // of cut. There's information about that in this document:
         // https://www.sorotec.de/webshop/Datenblaetter/fraeser/schnittwerte.pdf
@@ -297,17 +352,63 @@ impl Tool {
         // Sorotec document linked above.
         let e_f = 0.25;
 
-        // As for cutting tool wear factor, I might be misunderstanding the
-        // article, but I think the following should be a good worst case.
-        let t_f = 1.6;
-
-        // Now put it all together to calculate the tangential cutting
-        // force.
-        let tangential_cutting_force =
-            Force::from_value_n(sigma * a * z_c * e_f * t_f);
+        let tangential_cutting_force = tangential_force(a, z_c, e_f);
 
         let torque = tangential_cutting_force.to_torque(self.diameter);
 
         (tangential_cutting_force, torque)
     }
+
+    /// The tangential cutting force of an actual cut
+    ///
+    /// Same formula as [`Tool::tangential_cutting_force`], but with the depth
+    /// and width of cut, and the feed per tooth, that a program really uses.
+    pub fn cutting_force(
+        &self,
+        axial_depth: Length,
+        radial_depth: Length,
+        feed_per_tooth: Length,
+    ) -> Force {
+        let a = axial_depth.value_m() * feed_per_tooth.value_m();
+
+        // The engagement angle follows from the width of cut. 180° for a
+        // slot, less when cutting along a side.
+        let e_f = (radial_depth.value_m()
+            / self.diameter.to_length().value_m())
+        .clamp(0., 1.);
+        let engagement_angle = (1. - 2. * e_f).acos();
+        let z_c = (self.num_flutes * engagement_angle / TAU).ceil().max(1.);
+
+        tangential_force(a, z_c, e_f)
+    }
+}
+
+/// The tangential cutting force in aluminium
+///
+/// `a` is the cross-sectional area of the uncut chip in m², `z_c` the number
+/// of engaged teeth, `e_f` the engagement factor.
+fn tangential_force(a: f64, z_c: f64, e_f: f64) -> Force {
+    // Wikipedia has an article on ultimate tensile strength:
+    // https://en.wikipedia.org/wiki/Ultimate_tensile_strength
+    //
+    // According to the table in there, this is the value for aluminium:
+    let sigma = 483_000_000.; // Pascal
+
+    // As for cutting tool wear factor, I might be misunderstanding the
+    // article, but I think the following should be a good worst case.
+    let t_f = 1.6;
+
+    Force::from_value_n(sigma * a * z_c * e_f * t_f)
+}
+
+impl fmt::Display for Tool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Ø{} mm, {} mm cutting edge, {} flute(s)",
+            self.diameter.to_length().value_mm(),
+            self.length_cutting_edge.value_mm(),
+            self.num_flutes,
+        )
+    }
 }